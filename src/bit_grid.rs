@@ -0,0 +1,284 @@
+use crate::cell::Cell;
+use crate::grid::Grid;
+use crate::parallel::map_rows;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+
+// A bounded grid of two-state cells packed 64 to a word, so a Life-like rule
+// can be applied to 64 cells at a time. Bit i of word w in a row is the cell at x = 64w + i.
+#[derive(Clone, Debug, Hash, PartialEq)]
+pub struct BitGrid {
+    pub width: usize,
+    pub height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        let words_per_row = width.div_ceil(64);
+
+        BitGrid {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_pattern(pattern: &Pattern) -> BitGrid {
+        let mut bits = BitGrid::new(pattern.width as usize, pattern.height as usize);
+
+        for &(x, y) in pattern.alive_cells.iter() {
+            bits.set(x as usize, y as usize, true);
+        }
+
+        bits
+    }
+
+    pub fn to_pattern(&self) -> Pattern {
+        let alive_cells = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(x, y))
+            .map(|(x, y)| (x as u32, y as u32))
+            .collect();
+
+        Pattern::new(self.width as u32, self.height as u32, alive_cells)
+    }
+
+    // Converts today's grids, e.g. those the display code and older worlds use.
+    // Only alive cells are kept, as Life-like rules have no dying cells.
+    #[allow(dead_code)]
+    pub fn from_grid(grid: &Grid) -> BitGrid {
+        let mut bits = BitGrid::new(grid.cells[0].len(), grid.cells.len());
+
+        for cell in grid.cells.iter().flatten().filter(|cell| cell.alive) {
+            bits.set(cell.x as usize, cell.y as usize, true);
+        }
+
+        bits
+    }
+
+    // Uses the characters of the top left cell of the template grid.
+    #[allow(dead_code)]
+    pub fn to_grid(&self, template: &Grid) -> Grid {
+        let cell = &template.cells[0][0];
+
+        let cells = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let updated_cell = Cell::new_with_characters(
+                            x as u32,
+                            y as u32,
+                            cell.dead_character.clone(),
+                            cell.dying_character.clone(),
+                            cell.alive_character.clone(),
+                        );
+
+                        if self.get(x, y) {
+                            updated_cell.set_alive()
+                        } else {
+                            updated_cell
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Grid {
+            cells,
+            topology: template.topology,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.words[y * self.words_per_row + x / 64] & (1 << (x % 64)) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        let word = &mut self.words[y * self.words_per_row + x / 64];
+
+        if alive {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    pub fn population(&self) -> u64 {
+        self.words.iter().map(|w| w.count_ones() as u64).sum()
+    }
+
+    pub fn step(&self, rule: &Rule, threads: usize) -> BitGrid {
+        let rows = map_rows(self.height, threads, |y| {
+            (0..self.words_per_row)
+                .map(|w| {
+                    // Four bit planes holding each cell's alive neighbour count, 0 to 8.
+                    let mut counts = [0u64; 4];
+
+                    for dy in [-1i64, 0, 1].iter() {
+                        let row = y as i64 + dy;
+
+                        if row < 0 || row >= self.height as i64 {
+                            continue;
+                        }
+
+                        let (left, centre, right) = self.shifted(row as usize, w);
+
+                        add(&mut counts, left);
+                        add(&mut counts, right);
+
+                        if *dy != 0 {
+                            add(&mut counts, centre);
+                        }
+                    }
+
+                    let alive = self.words[y * self.words_per_row + w];
+
+                    let born = rule
+                        .birth
+                        .iter()
+                        .fold(0, |acc, &n| acc | equals(&counts, n));
+                    let survived = rule
+                        .survival
+                        .iter()
+                        .fold(0, |acc, &n| acc | equals(&counts, n));
+
+                    ((!alive & born) | (alive & survived)) & self.mask(w)
+                })
+                .collect::<Vec<u64>>()
+        });
+
+        BitGrid {
+            width: self.width,
+            height: self.height,
+            words_per_row: self.words_per_row,
+            words: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    // A word of a row, alongside the same word with each cell replaced by its left
+    // neighbour, and by its right neighbour, carrying bits across word boundaries.
+    fn shifted(&self, y: usize, w: usize) -> (u64, u64, u64) {
+        let row = &self.words[y * self.words_per_row..(y + 1) * self.words_per_row];
+
+        let centre = row[w];
+        let previous = if w > 0 { row[w - 1] } else { 0 };
+        let next = if w + 1 < self.words_per_row {
+            row[w + 1]
+        } else {
+            0
+        };
+
+        let left = (centre << 1) | (previous >> 63);
+        let right = (centre >> 1) | (next << 63);
+
+        (left, centre, right)
+    }
+
+    // The bits of a word which are inside the grid.
+    fn mask(&self, w: usize) -> u64 {
+        let remaining = self.width - w * 64;
+
+        if remaining >= 64 {
+            !0
+        } else {
+            (1 << remaining) - 1
+        }
+    }
+}
+
+// Adds one to the bit-sliced count of every cell whose bit is set.
+fn add(counts: &mut [u64; 4], bits: u64) {
+    let mut carry = bits;
+
+    for plane in counts.iter_mut() {
+        let next_carry = *plane & carry;
+        *plane ^= carry;
+        carry = next_carry;
+    }
+}
+
+// The cells whose bit-sliced count is n.
+fn equals(counts: &[u64; 4], n: usize) -> u64 {
+    counts.iter().enumerate().fold(!0, |acc, (i, &plane)| {
+        if n & (1 << i) != 0 {
+            acc & plane
+        } else {
+            acc & !plane
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_of_life::GameOfLife;
+    use crate::life_like::LifeLike;
+    use crate::world::Simulation;
+
+    #[test]
+    #[rustfmt::skip]
+    fn converts_to_and_from_grid() {
+        let grid = Grid::new_alive_grid(
+            70, 3,
+            String::from("."), String::from("x"), String::from("*"),
+            vec![(0, 0), (63, 1), (64, 1), (69, 2)],
+            vec![],
+        );
+
+        let bits = BitGrid::from_grid(&grid);
+
+        assert_eq!(bits.population(), 4);
+        assert!(bits.get(63, 1));
+        assert!(bits.get(64, 1));
+        assert_eq!(bits.to_grid(&grid), grid);
+        assert_eq!(BitGrid::from_pattern(&bits.to_pattern()), bits);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn step_matches_game_of_life_across_word_boundaries() {
+        let grid = Grid::new_alive_grid(
+            130, 6,
+            String::new(), String::new(), String::new(),
+            vec![
+                (62, 1), (63, 1), (64, 1),
+                (0, 2), (0, 3), (0, 4),
+                (127, 2), (128, 2), (129, 2), (128, 3),
+                (10, 5), (11, 5), (12, 5),
+            ],
+            vec![],
+        );
+
+        let mut bits = BitGrid::from_grid(&grid);
+        let mut world = GameOfLife { grid, seed: 0 };
+
+        for _ in 0..10 {
+            bits = bits.step(&Rule::conway(), 1);
+            world.next();
+
+            assert_eq!(bits.to_grid(&world.grid), world.grid);
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn step_matches_life_like_rule_with_birth_on_zero() {
+        let grid = Grid::new_alive_grid(
+            5, 5,
+            String::new(), String::new(), String::new(),
+            vec![(1, 1), (2, 2), (3, 2)],
+            vec![],
+        );
+
+        let rule = Rule::parse("B0123/S0").unwrap();
+        let bits = BitGrid::from_grid(&grid).step(&rule, 1);
+
+        let mut world = LifeLike { grid, seed: 0, rule };
+        world.next();
+
+        assert_eq!(bits.to_grid(&world.grid), world.grid);
+    }
+}
@@ -1,6 +1,6 @@
-use crate::cell::Cell;
+use crate::generations::Generations;
 use crate::grid::Grid;
-use crate::neighbours::find_neighbours;
+use crate::rule::GenerationsRule;
 use crate::world::Simulation;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -9,6 +9,10 @@ pub struct BriansBrain {
     pub seed: u32,
 }
 
+// A cell turns on if it was off but had exactly two neighbors that were on.
+// All cells that were "on" go into the "dying" state.
+// Cells that were in the dying state go into the off state.
+// This is the Generations rule /2/3.
 impl Simulation for BriansBrain {
     fn seed(&self) -> &u32 {
         &self.seed
@@ -18,75 +22,27 @@ impl Simulation for BriansBrain {
         &self.grid
     }
 
-    fn next(&mut self) {
-        let width = self.grid.cells[0].len();
-        let height = self.grid.cells.len();
-
-        let mut updated_cells: Vec<Vec<Cell>> = vec![];
-
-        for y in 0..height {
-            let mut row: Vec<Cell> = vec![];
-            for x in 0..width {
-                let cell = &self.grid.cells[y][x];
-                let neighbours = find_neighbours(&self.grid, cell);
-
-                if BriansBrain::is_alive(cell, neighbours) {
-                    row.push(
-                        Cell::new_with_characters(
-                            x as u32,
-                            y as u32,
-                            cell.dead_character.clone(),
-                            cell.dying_character.clone(),
-                            cell.alive_character.clone(),
-                        )
-                        .set_alive(),
-                    )
-                } else if BriansBrain::is_dying(cell) {
-                    row.push(
-                        Cell::new_with_characters(
-                            x as u32,
-                            y as u32,
-                            cell.dead_character.clone(),
-                            cell.dying_character.clone(),
-                            cell.alive_character.clone(),
-                        )
-                        .set_dying(),
-                    )
-                } else {
-                    row.push(Cell::new_with_characters(
-                        x as u32,
-                        y as u32,
-                        cell.dead_character.clone(),
-                        cell.dying_character.clone(),
-                        cell.alive_character.clone(),
-                    ))
-                }
-            }
-            updated_cells.push(row);
-        }
-
-        self.grid.cells = updated_cells;
+    fn rule(&self) -> String {
+        GenerationsRule::brians_brain().to_string()
     }
-}
 
-// A cell turns on if it was off but had exactly two neighbors that were on.
-// All cells that were "on" go into the "dying" state.
-// Cells that were in the dying state go into the off state.
-impl BriansBrain {
-    pub fn is_dying(cell: &Cell) -> bool {
-        cell.alive
+    fn next(&mut self) {
+        self.next_parallel(1)
     }
 
-    pub fn is_alive(cell: &Cell, neighbours: Vec<&Cell>) -> bool {
-        let alive_neighbours_count = neighbours.iter().filter(|&c| c.alive).count();
-
-        cell.alive == false && cell.dying == false && alive_neighbours_count == 2
+    fn next_parallel(&mut self, threads: usize) {
+        self.grid.cells =
+            Generations::next_cells(&self.grid, &GenerationsRule::brians_brain(), threads);
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
+    use crate::cell::Cell;
+    use crate::test_worlds::{assert_parallel_matches_sequential, scattered_grid};
+    use crate::world::World;
 
     #[test]
     fn world_preserves_seed() {
@@ -288,7 +244,8 @@ mod tests {
 
         let neighbours = vec![cell_one, cell_two, cell_three];
 
-        let is_alive = BriansBrain::is_alive(&alive_cell, neighbours);
+        let is_alive =
+            Generations::is_alive(&GenerationsRule::brians_brain(), &alive_cell, neighbours);
         assert_eq!(is_alive, true);
     }
 
@@ -302,10 +259,12 @@ mod tests {
 
         let neighbours = vec![cell_one, cell_two, cell_three];
 
-        let is_alive = BriansBrain::is_alive(&alive_cell, neighbours);
+        let is_alive =
+            Generations::is_alive(&GenerationsRule::brians_brain(), &alive_cell, neighbours);
         assert_eq!(is_alive, false);
 
-        let is_dying = BriansBrain::is_dying(&alive_cell);
+        let is_dying =
+            GenerationsRule::brians_brain().next_decay(alive_cell.alive, alive_cell.decay) > 0;
         assert_eq!(is_dying, true);
     }
 
@@ -319,10 +278,50 @@ mod tests {
 
         let neighbours = vec![cell_one, cell_two, cell_three];
 
-        let is_alive = BriansBrain::is_alive(&alive_cell, neighbours);
+        let is_alive =
+            Generations::is_alive(&GenerationsRule::brians_brain(), &alive_cell, neighbours);
         assert_eq!(is_alive, false);
 
-        let is_dying = BriansBrain::is_dying(&alive_cell);
+        let is_dying =
+            GenerationsRule::brians_brain().next_decay(alive_cell.alive, alive_cell.decay) > 0;
         assert_eq!(is_dying, true);
     }
+
+    #[test]
+    fn parallel_step_matches_sequential_step() {
+        assert_parallel_matches_sequential(
+            World::from_ruleset("brians_brain", scattered_grid(), 0).unwrap(),
+        );
+    }
+
+    /*
+
+       . . . .      . 1 1 .
+       . * * .      . 0 0 .
+       . . . .  ->  . 1 1 .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn cells_never_survive_so_are_never_older_than_one() {
+        let grid = Grid::new_alive_grid(
+            4, 3,
+            String::new(), String::new(), String::new(),
+            vec![(1, 1), (2, 1)],
+            vec![],
+        );
+        let mut world = BriansBrain { grid, seed: 0 };
+
+        world.next();
+
+        assert_eq!(
+            world.ages(),
+            vec![
+                vec![0, 1, 1, 0],
+                vec![0, 0, 0, 0],
+                vec![0, 1, 1, 0],
+            ]
+        );
+        assert!(world.grid.cells[1][1].dying);
+    }
 }
@@ -1,4 +1,6 @@
-#[derive(Clone, Debug, PartialEq)]
+use std::cmp;
+
+#[derive(Clone, Debug)]
 pub struct Cell {
     pub x: u32,
     pub y: u32,
@@ -7,9 +9,28 @@ pub struct Cell {
     pub alive_character: String,
     pub dying: bool,
     pub alive: bool,
+    pub decay: u32,
+    // How many generations in a row the cell has been alive; zero when it isn't.
+    pub age: u32,
+}
+
+// Age is left out, so that cells in the same state are equal however long they've been in it,
+// e.g. a still life is the same grid from one generation to the next.
+impl PartialEq for Cell {
+    fn eq(&self, other: &Cell) -> bool {
+        self.x == other.x
+            && self.y == other.y
+            && self.dead_character == other.dead_character
+            && self.dying_character == other.dying_character
+            && self.alive_character == other.alive_character
+            && self.dying == other.dying
+            && self.alive == other.alive
+            && self.decay == other.decay
+    }
 }
 
 impl Cell {
+    #[allow(dead_code)]
     pub fn new(x: u32, y: u32) -> Cell {
         Cell {
             x,
@@ -19,6 +40,8 @@ impl Cell {
             alive_character: String::from("*"),
             dying: false,
             alive: false,
+            decay: 0,
+            age: 0,
         }
     }
 
@@ -37,14 +60,17 @@ impl Cell {
             alive_character,
             dying: false,
             alive: false,
+            decay: 0,
+            age: 0,
         }
     }
 
-    #[allow(dead_code)]
     pub fn set_dead(&self) -> Cell {
         Cell {
             dying: false,
             alive: false,
+            decay: 0,
+            age: 0,
             dead_character: self.dead_character.clone(),
             dying_character: self.dying_character.clone(),
             alive_character: self.alive_character.clone(),
@@ -52,11 +78,12 @@ impl Cell {
         }
     }
 
-    #[allow(dead_code)]
     pub fn set_dying(&self) -> Cell {
         Cell {
             dying: true,
             alive: false,
+            decay: 1,
+            age: 0,
             dead_character: self.dead_character.clone(),
             dying_character: self.dying_character.clone(),
             alive_character: self.alive_character.clone(),
@@ -68,6 +95,8 @@ impl Cell {
         Cell {
             dying: false,
             alive: true,
+            decay: 0,
+            age: 1,
             dead_character: self.dead_character.clone(),
             dying_character: self.dying_character.clone(),
             alive_character: self.alive_character.clone(),
@@ -75,11 +104,42 @@ impl Cell {
         }
     }
 
+    // Alive in the generation after `previous`, one generation older if it was already alive.
+    pub fn set_alive_after(&self, previous: &Cell) -> Cell {
+        Cell {
+            age: if previous.alive { previous.age + 1 } else { 1 },
+            ..self.set_alive()
+        }
+    }
+
+    // Dying for `decay` generations; zero means dead.
+    pub fn set_decaying(&self, decay: u32) -> Cell {
+        Cell {
+            dying: decay > 0,
+            alive: false,
+            decay,
+            age: 0,
+            dead_character: self.dead_character.clone(),
+            dying_character: self.dying_character.clone(),
+            alive_character: self.alive_character.clone(),
+            ..*self
+        }
+    }
+
+    // Dying characters separated by commas, e.g. "x,o,-", draw each decay stage with
+    // its own character, and any further stages reuse the last one.
     pub fn display(&self) -> &str {
         if self.alive {
             &self.alive_character[..]
         } else if self.dying {
-            &self.dying_character[..]
+            if self.dying_character.len() > 1 && self.dying_character.contains(',') {
+                let stages: Vec<&str> = self.dying_character.split(',').collect();
+                let stage = cmp::min(cmp::max(self.decay as usize, 1), stages.len()) - 1;
+
+                stages[stage]
+            } else {
+                &self.dying_character[..]
+            }
         } else {
             &self.dead_character[..]
         }
@@ -112,4 +172,40 @@ mod tests {
 
         assert_eq!(alive_cell.display(), "*");
     }
+
+    #[test]
+    fn print_decay_stage_character_when_decaying() {
+        let cell = Cell::new_with_characters(
+            0,
+            0,
+            String::from("."),
+            String::from("x,o,-"),
+            String::from("*"),
+        );
+
+        assert_eq!(cell.set_decaying(1).display(), "x");
+        assert_eq!(cell.set_decaying(2).display(), "o");
+        assert_eq!(cell.set_decaying(3).display(), "-");
+        assert_eq!(cell.set_decaying(4).display(), "-");
+    }
+
+    #[test]
+    fn print_whole_dying_string_without_commas() {
+        let cell = Cell::new_with_characters(
+            0,
+            0,
+            String::from("."),
+            String::from("xo"),
+            String::from("*"),
+        );
+
+        assert_eq!(cell.set_decaying(2).display(), "xo");
+    }
+
+    #[test]
+    fn decaying_to_zero_is_dead() {
+        let cell = Cell::new(0, 0).set_dying().set_decaying(0);
+
+        assert_eq!(cell, Cell::new(0, 0));
+    }
 }
@@ -1,13 +1,58 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use std::time::Duration;
+
+use crate::colour::{Palette, Theme};
+use crate::cycle::OnStable;
+use crate::grid::RenderMode;
+use crate::neighbours::Topology;
+use crate::stats::StatsFormat;
+use crate::stop;
+use crate::viewport::MAX_ZOOM;
 
 #[derive(Parser)]
+#[clap(after_help = "EXIT CODES:
+    0    after the last generation
+    1    with an error
+    2    with bad arguments
+    3    as the world died out
+    4    as the world stabilised, with --on-stable stop
+    5    as the population crossed a threshold
+    6    after the timeout
+    130  when interrupted with Ctrl-C")]
 pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
     /// options: gosper
     #[clap(short, long)]
     pub preset: Option<String>,
-    /// options: game_of_life (default), brians_brain
+    /// A pattern file in RLE (.rle), plaintext (.cells), Life 1.05/1.06 (.lif or .life)
+    /// or macrocell (.mc) format, placed in the middle of the world.
+    /// The rule saved in the file is used unless a ruleset is given
+    #[clap(short = 'f', long)]
+    pub pattern_file: Option<String>,
+    /// options: game_of_life (default), brians_brain,
+    /// a Life-like rulestring such as B36/S23 (HighLife) or B2/S (Seeds),
+    /// or a Generations rulestring such as 345/2/4 (Star Wars)
     #[clap(short, long)]
     pub ruleset: Option<String>,
+    /// How the world is stored and stepped
+    /// options: dense (default), sparse (an unbounded plane, Life-like rules only),
+    /// hashlife (an unbounded plane stepped with HashLife, Life-like rules only),
+    /// packed (a bounded grid stepped 64 cells at a time, Life-like rules only)
+    #[clap(short, long)]
+    pub engine: Option<String>,
+    /// With the hashlife engine, each generation shown is 2^step_exponent generations apart.
+    /// Default is 0
+    #[clap(short = 'k', long)]
+    pub step_exponent: Option<u8>,
+    /// Number of threads used to step each generation of the dense and packed engines.
+    /// Default is 1
+    #[clap(short = 'j', long)]
+    pub threads: Option<usize>,
+    /// How the edges of the world join together
+    /// options: bounded (default), torus, klein_bottle, cross_surface
+    #[clap(short, long)]
+    pub topology: Option<String>,
     /// Width, in characters, of the world
     /// Default is 40
     #[clap(short, long)]
@@ -16,6 +61,30 @@ pub struct Cli {
     /// Default is 40
     #[clap(short, long)]
     pub height: Option<u32>,
+    /// How cells are drawn
+    /// options: text (default, a character per cell), half_block (1x2 cells per character),
+    /// braille (2x4 cells per character), age (as text, shading alive cells by how long
+    /// they've been alive, dense engine only). Edit mode always uses text
+    #[clap(long)]
+    pub render: Option<String>,
+    /// How many cells across and down each character shows, drawn shaded by how many are alive.
+    /// auto zooms out until the whole world fits on the terminal. Default is 1
+    #[clap(short = 'z', long)]
+    pub zoom: Option<String>,
+    /// Keep the alive cells in the middle of the terminal as they move
+    #[clap(long)]
+    pub follow: bool,
+    /// Colours to draw cells in
+    /// options: auto (default, off when not writing to a terminal or when NO_COLOR is set
+    /// and not empty),
+    /// none, 16, 256, truecolour
+    #[clap(long)]
+    pub colour: Option<String>,
+    /// Named set of colours for dead, alive and dying cells, with a colour for each
+    /// decay stage of Generations rules
+    /// options: classic (default), fire, ocean, forest, mono
+    #[clap(long)]
+    pub theme: Option<String>,
     /// Number of cells that start alive, defaults to 40
     #[clap(short, long)]
     pub num_starting_cells: Option<u32>,
@@ -25,7 +94,8 @@ pub struct Cli {
     /// Character which represents a dead cell
     #[clap(short, long)]
     pub dead_char: Option<String>,
-    /// Character which represents a dying cell
+    /// Character which represents a dying cell,
+    /// or one per decay stage of Generations rules separated by commas, e.g. "x,o,-"
     #[clap(short = 'x', long)]
     pub dying_char: Option<String>,
     /// Character which represents an alive cell
@@ -35,4 +105,174 @@ pub struct Cli {
     /// Default is 250
     #[clap(short, long)]
     pub gen_length: Option<u64>,
+    /// What to do once the world repeats itself, as a still life, an oscillator or by dying out,
+    /// besides reporting it
+    /// options: continue (default), stop, reseed (with a new random world)
+    #[clap(long)]
+    pub on_stable: Option<String>,
+    /// Run with an interactive terminal UI: pause, step, step back, change speed and reseed
+    /// from the keyboard
+    #[clap(short, long)]
+    pub interactive: bool,
+    /// Run without drawing the world or waiting between generations, printing only a summary
+    /// of the last generation, for scripts. Snapshots and statistics are still saved
+    #[clap(long, conflicts_with_all = &["interactive", "edit"])]
+    pub headless: bool,
+    /// Generations to run for after generation 0 before stopping, or forever.
+    /// Default is 1000
+    #[clap(long, conflicts_with_all = &["interactive", "edit"])]
+    pub generations: Option<String>,
+    /// Stop once nothing is alive
+    #[clap(long, conflicts_with_all = &["interactive", "edit"])]
+    pub stop_on_extinction: bool,
+    /// Stop once the population is above this
+    #[clap(long, conflicts_with_all = &["interactive", "edit"])]
+    pub stop_above: Option<u64>,
+    /// Stop once the population is below this
+    #[clap(long, conflicts_with_all = &["interactive", "edit"])]
+    pub stop_below: Option<u64>,
+    /// Stop after running for this many seconds. The exit code says why a run stopped
+    #[clap(long, conflicts_with_all = &["interactive", "edit"])]
+    pub timeout: Option<f64>,
+    /// Start the interactive terminal UI in edit mode, to draw a starting pattern.
+    /// Draws on an empty world unless a preset, pattern file or seed is given
+    #[clap(short = 'E', long)]
+    pub edit: bool,
+    /// File that drawings from edit mode are saved to, in the format given by the extension.
+    /// Default is drawing.rle
+    #[clap(long)]
+    pub drawing_file: Option<String>,
+    /// Generation at which to save the world to a pattern file, can be given more than once
+    #[clap(long, multiple_occurrences(true))]
+    pub snapshot_at: Vec<u64>,
+    /// File that snapshots are saved to, with the generation added to its name.
+    /// The format is given by the extension: .rle, .cells, .lif (Life 1.06), .life (Life 1.05)
+    /// or .mc.
+    /// Default is snapshot.rle, giving snapshot_100.rle for generation 100
+    #[clap(long)]
+    pub snapshot_file: Option<String>,
+    /// File that the population, births, deaths, bounding box and dying cells of each generation
+    /// are written to as the world runs, as CSV or JSON given by the extension: .csv or .json
+    #[clap(long)]
+    pub stats_out: Option<String>,
+}
+
+// The options given by name or number, checked before anything runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    pub topology: Topology,
+    pub render_mode: RenderMode,
+    pub zoom: u32,
+    pub on_stable: OnStable,
+    pub generations: Option<u64>,
+    pub timeout: Option<Duration>,
+    pub palette: Option<Palette>,
+    pub theme: Theme,
+}
+
+impl Cli {
+    // Auto zoom starts at 1, and is worked out once the world is built.
+    pub fn options(&self) -> Result<Options, String> {
+        match self.engine.as_deref() {
+            None | Some("dense") | Some("sparse") | Some("hashlife") | Some("packed") => (),
+            Some(engine) => return Err(format!("Unknown engine: {}", engine)),
+        }
+
+        if let Some(ref path) = self.stats_out {
+            StatsFormat::from_extension(path)
+                .ok_or_else(|| format!("Statistics file must end in .csv or .json: {}", path))?;
+        }
+
+        let zoom = match self.zoom.as_deref() {
+            None | Some("auto") => 1,
+            Some(zoom) => zoom
+                .parse::<u32>()
+                .ok()
+                .filter(|&zoom| (1..=MAX_ZOOM).contains(&zoom))
+                .ok_or_else(|| format!("Zoom must be auto or 1 to {}, got: {}", MAX_ZOOM, zoom))?,
+        };
+
+        let timeout = match self.timeout {
+            Some(t) if !t.is_finite() || t < 0.0 => {
+                return Err(format!("Timeout must be a number of seconds, got: {}", t))
+            }
+            t => t.map(Duration::from_secs_f64),
+        };
+
+        let theme_name = self.theme.as_deref().unwrap_or("classic");
+
+        Ok(Options {
+            topology: Topology::parse(self.topology.as_deref().unwrap_or("bounded"))?,
+            render_mode: RenderMode::parse(self.render.as_deref().unwrap_or("text"))?,
+            zoom,
+            on_stable: OnStable::parse(self.on_stable.as_deref().unwrap_or("continue"))?,
+            generations: stop::parse_generations(self.generations.as_deref().unwrap_or("1000"))?,
+            timeout,
+            palette: Palette::parse(self.colour.as_deref().unwrap_or("auto"))?,
+            theme: Theme::find(theme_name)
+                .ok_or_else(|| format!("Unknown theme: {}", theme_name))?,
+        })
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run many random soups until they settle, and count the still lifes, oscillators and
+    /// spaceships left behind by their apgcodes, e.g. xs4_33 for a block.
+    /// Soups are made as usual from --width, --height, --num-starting-cells and --ruleset,
+    /// which must be Life-like
+    Search {
+        /// Number of soups to run. Default is 1000
+        #[clap(long)]
+        soups: Option<u32>,
+        /// Seed of the first soup, with the rest following on. Default is 1
+        #[clap(long)]
+        first_seed: Option<u32>,
+        /// Most generations a soup is run for to settle. Default is 5000
+        #[clap(long)]
+        max_generations: Option<u64>,
+        /// Objects found in at most this many soups are reported with their seeds. Default is 3
+        #[clap(long)]
+        rare: Option<usize>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> Result<Options, String> {
+        let args = std::iter::once("game_of_life").chain(args.iter().copied());
+
+        Cli::try_parse_from(args).unwrap().options()
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let options = options(&[]).unwrap();
+
+        assert_eq!(options.topology, Topology::Bounded);
+        assert_eq!(options.on_stable, OnStable::Continue);
+        assert_eq!(options.generations, Some(1000));
+        assert_eq!(options.zoom, 1);
+    }
+
+    // main exits with stop::BAD_ARGUMENTS for each of these, as clap does for its own errors.
+    #[test]
+    fn bad_names_and_numbers_are_rejected() {
+        for bad in [
+            ["--generations", "abc"],
+            ["--topology", "sphere"],
+            ["--render", "foo"],
+            ["--on-stable", "x"],
+            ["--colour", "17"],
+            ["--engine", "foo"],
+            ["--theme", "dusk"],
+            ["--zoom", "0"],
+            ["--timeout", "nan"],
+            ["--stats-out", "run.txt"],
+        ] {
+            assert!(options(&bad).is_err(), "{:?} was accepted", bad);
+        }
+    }
 }
@@ -0,0 +1,384 @@
+use std::ffi::OsStr;
+
+use crossterm::style::Color;
+
+use crate::cell::Cell;
+
+type Rgb = (u8, u8, u8);
+
+// How many colours the terminal can show.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Palette {
+    Ansi16,
+    Ansi256,
+    TrueColour,
+}
+
+impl Palette {
+    // "auto" picks a palette for the terminal, and "none" turns colour off.
+    pub fn parse(name: &str) -> Result<Option<Palette>, String> {
+        match name {
+            "auto" => Ok(Palette::detect()),
+            "none" | "off" => Ok(None),
+            "16" => Ok(Some(Palette::Ansi16)),
+            "256" => Ok(Some(Palette::Ansi256)),
+            name if name.starts_with("true") || name == "24bit" => Ok(Some(Palette::TrueColour)),
+            name => Err(format!("Unknown colour palette: {}", name)),
+        }
+    }
+
+    pub fn detect() -> Option<Palette> {
+        use crossterm::tty::IsTty;
+        use std::env;
+        use std::io;
+
+        Palette::choose(
+            env::var_os("NO_COLOR").as_deref(),
+            io::stdout().is_tty(),
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    // Colour is off when NO_COLOR is set to anything but an empty string, as no-color.org asks,
+    // or when stdout isn't a terminal, e.g. when piped to a file.
+    fn choose(
+        no_color: Option<&OsStr>,
+        is_tty: bool,
+        colorterm: Option<&str>,
+        term: Option<&str>,
+    ) -> Option<Palette> {
+        if no_color.is_some_and(|value| !value.is_empty()) || !is_tty {
+            None
+        } else if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+            Some(Palette::TrueColour)
+        } else if term.is_some_and(|t| t.contains("256")) {
+            Some(Palette::Ansi256)
+        } else {
+            Some(Palette::Ansi16)
+        }
+    }
+
+    pub fn colour(&self, rgb: Rgb) -> Color {
+        match self {
+            Palette::TrueColour => Color::Rgb {
+                r: rgb.0,
+                g: rgb.1,
+                b: rgb.2,
+            },
+            Palette::Ansi256 => Color::AnsiValue(nearest_256(rgb)),
+            Palette::Ansi16 => nearest_16(rgb),
+        }
+    }
+}
+
+// How many colours cells are shaded with by age.
+const AGE_STAGES: usize = 8;
+
+// A named set of colours. Dying cells fade from dying_start to dying_end over their decay stages,
+// and when shading by age, alive cells fade from alive to old.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub dead: Rgb,
+    pub alive: Rgb,
+    pub old: Rgb,
+    pub dying_start: Rgb,
+    pub dying_end: Rgb,
+}
+
+impl Theme {
+    pub fn find(name: &str) -> Option<Theme> {
+        let theme = |dead, alive, old, dying_start, dying_end| Theme {
+            dead,
+            alive,
+            old,
+            dying_start,
+            dying_end,
+        };
+
+        match name {
+            "classic" => Some(theme(
+                (88, 88, 88),
+                (80, 220, 100),
+                (60, 110, 230),
+                (230, 200, 60),
+                (150, 50, 30),
+            )),
+            "fire" => Some(theme(
+                (70, 40, 30),
+                (255, 240, 150),
+                (200, 40, 40),
+                (255, 140, 0),
+                (110, 20, 0),
+            )),
+            "ocean" => Some(theme(
+                (30, 60, 90),
+                (120, 220, 255),
+                (10, 80, 140),
+                (30, 120, 200),
+                (20, 40, 100),
+            )),
+            "forest" => Some(theme(
+                (60, 70, 40),
+                (160, 230, 90),
+                (30, 100, 40),
+                (200, 170, 60),
+                (100, 70, 30),
+            )),
+            "mono" => Some(theme(
+                (90, 90, 90),
+                (255, 255, 255),
+                (150, 150, 150),
+                (190, 190, 190),
+                (120, 120, 120),
+            )),
+            _ => None,
+        }
+    }
+}
+
+// The colour of each cell state, for a theme shown with a palette.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colours {
+    dead: Color,
+    alive: Color,
+    dying: Vec<Color>,
+    ages: Vec<Color>,
+}
+
+impl Colours {
+    // Gives each of the rule's decay stages its own colour.
+    pub fn new(theme: &Theme, palette: Palette, decay_stages: u32) -> Colours {
+        let fade = |from, to, stages: usize| -> Vec<Color> {
+            (0..stages)
+                .map(|stage| {
+                    let t = if stages > 1 {
+                        stage as f64 / (stages - 1) as f64
+                    } else {
+                        0.0
+                    };
+
+                    palette.colour(blend(from, to, t))
+                })
+                .collect()
+        };
+
+        Colours {
+            dead: palette.colour(theme.dead),
+            alive: palette.colour(theme.alive),
+            dying: fade(
+                theme.dying_start,
+                theme.dying_end,
+                decay_stages.max(1) as usize,
+            ),
+            ages: fade(theme.alive, theme.old, AGE_STAGES),
+        }
+    }
+
+    // Decay stages past the last colour reuse it.
+    pub fn of(&self, cell: &Cell) -> Color {
+        if cell.alive {
+            self.alive
+        } else if cell.dying {
+            let stage = (cell.decay.max(1) as usize).min(self.dying.len()) - 1;
+            self.dying[stage]
+        } else {
+            self.dead
+        }
+    }
+
+    // As `of`, but alive cells are coloured by how long they've been alive.
+    pub fn by_age(&self, cell: &Cell) -> Color {
+        if cell.alive {
+            self.ages[age_stage(cell.age, self.ages.len())]
+        } else {
+            self.of(cell)
+        }
+    }
+}
+
+// Which of `stages` an age falls in, each twice as long as the one before:
+// 1, 2 to 3, 4 to 7, and so on, with the last stage holding every age after.
+pub fn age_stage(age: u32, stages: usize) -> usize {
+    let stage = 31 - age.max(1).leading_zeros() as usize;
+
+    stage.min(stages - 1)
+}
+
+fn blend(from: Rgb, to: Rgb, t: f64) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+
+    (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+fn distance(a: Rgb, b: Rgb) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+// The nearest of the 6x6x6 colour cube (16 to 231) and the grey ramp (232 to 255).
+fn nearest_256(rgb: Rgb) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - c as i32).abs())
+            .unwrap()
+    };
+
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (LEVELS[r], LEVELS[g], LEVELS[b]);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey_index = ((average.saturating_sub(8)) / 10).min(23) as u8;
+    let grey = 8 + grey_index * 10;
+
+    if distance(rgb, (grey, grey, grey)) < distance(rgb, cube) {
+        232 + grey_index
+    } else {
+        (16 + 36 * r + 6 * g + b) as u8
+    }
+}
+
+fn nearest_16(rgb: Rgb) -> Color {
+    let colours = [
+        (Color::Black, (0, 0, 0)),
+        (Color::DarkRed, (128, 0, 0)),
+        (Color::DarkGreen, (0, 128, 0)),
+        (Color::DarkYellow, (128, 128, 0)),
+        (Color::DarkBlue, (0, 0, 128)),
+        (Color::DarkMagenta, (128, 0, 128)),
+        (Color::DarkCyan, (0, 128, 128)),
+        (Color::Grey, (192, 192, 192)),
+        (Color::DarkGrey, (128, 128, 128)),
+        (Color::Red, (255, 0, 0)),
+        (Color::Green, (0, 255, 0)),
+        (Color::Yellow, (255, 255, 0)),
+        (Color::Blue, (0, 0, 255)),
+        (Color::Magenta, (255, 0, 255)),
+        (Color::Cyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+
+    colours
+        .iter()
+        .min_by_key(|(_, c)| distance(rgb, *c))
+        .map(|(colour, _)| *colour)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colour_is_off_without_a_terminal_or_with_no_color() {
+        let set = Some(OsStr::new("1"));
+
+        assert_eq!(Palette::choose(set, true, Some("truecolor"), None), None);
+        assert_eq!(Palette::choose(None, false, Some("truecolor"), None), None);
+        assert_eq!(
+            Palette::choose(None, true, Some("truecolor"), None),
+            Some(Palette::TrueColour)
+        );
+        assert_eq!(
+            Palette::choose(None, true, None, Some("xterm-256color")),
+            Some(Palette::Ansi256)
+        );
+        assert_eq!(
+            Palette::choose(None, true, None, Some("xterm")),
+            Some(Palette::Ansi16)
+        );
+    }
+
+    #[test]
+    fn empty_no_color_leaves_colour_on() {
+        assert_eq!(
+            Palette::choose(Some(OsStr::new("")), true, None, Some("xterm")),
+            Some(Palette::Ansi16)
+        );
+    }
+
+    #[test]
+    fn nearest_palette_colours() {
+        assert_eq!(Palette::Ansi256.colour((255, 0, 0)), Color::AnsiValue(196));
+        assert_eq!(Palette::Ansi256.colour((0, 0, 0)), Color::AnsiValue(16));
+        assert_eq!(
+            Palette::Ansi256.colour((128, 128, 128)),
+            Color::AnsiValue(244)
+        );
+        assert_eq!(Palette::Ansi16.colour((250, 20, 10)), Color::Red);
+        assert_eq!(Palette::Ansi16.colour((100, 100, 100)), Color::DarkGrey);
+        assert_eq!(
+            Palette::TrueColour.colour((1, 2, 3)),
+            Color::Rgb { r: 1, g: 2, b: 3 }
+        );
+    }
+
+    #[test]
+    fn each_decay_stage_has_its_own_colour() {
+        let theme = Theme::find("classic").unwrap();
+        let colours = Colours::new(&theme, Palette::TrueColour, 3);
+
+        let dying = |decay| colours.of(&Cell::new(0, 0).set_decaying(decay));
+
+        assert_eq!(dying(1), Palette::TrueColour.colour(theme.dying_start));
+        assert_eq!(dying(2), Palette::TrueColour.colour((190, 125, 45)));
+        assert_eq!(dying(3), Palette::TrueColour.colour(theme.dying_end));
+        assert_eq!(dying(9), dying(3));
+        assert_eq!(
+            colours.of(&Cell::new(0, 0).set_alive()),
+            Palette::TrueColour.colour(theme.alive)
+        );
+        assert_eq!(
+            colours.of(&Cell::new(0, 0)),
+            Palette::TrueColour.colour(theme.dead)
+        );
+    }
+
+    #[test]
+    fn older_cells_fade_from_alive_to_old() {
+        let theme = Theme::find("classic").unwrap();
+        let colours = Colours::new(&theme, Palette::TrueColour, 1);
+
+        let aged = |age| Cell {
+            age,
+            ..Cell::new(0, 0).set_alive()
+        };
+
+        assert_eq!(
+            colours.by_age(&aged(1)),
+            Palette::TrueColour.colour(theme.alive)
+        );
+        assert_eq!(
+            colours.by_age(&aged(500)),
+            Palette::TrueColour.colour(theme.old)
+        );
+        assert_ne!(colours.by_age(&aged(4)), colours.by_age(&aged(8)));
+        assert_eq!(
+            colours.by_age(&Cell::new(0, 0)),
+            colours.of(&Cell::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn ages_double_in_length_each_stage() {
+        let stages: Vec<usize> = [0, 1, 2, 3, 4, 7, 8, 1000]
+            .iter()
+            .map(|&age| age_stage(age, 4))
+            .collect();
+
+        assert_eq!(stages, vec![0, 0, 1, 1, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn parse_palettes_and_find_themes() {
+        assert_eq!(Palette::parse("none"), Ok(None));
+        assert_eq!(Palette::parse("256"), Ok(Some(Palette::Ansi256)));
+        assert_eq!(Palette::parse("truecolour"), Ok(Some(Palette::TrueColour)));
+        assert!(Palette::parse("8").is_err());
+        assert!(Theme::find("fire").is_some());
+        assert!(Theme::find("neon").is_none());
+    }
+}
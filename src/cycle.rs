@@ -0,0 +1,773 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::objects::{self, Connectivity};
+use crate::viewport;
+use crate::world::Simulation;
+
+// What happens when the world stops changing, other than reporting it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OnStable {
+    Continue,
+    Stop,
+    Reseed,
+}
+
+impl OnStable {
+    pub fn parse(name: &str) -> Result<OnStable, String> {
+        match name {
+            "continue" => Ok(OnStable::Continue),
+            "stop" => Ok(OnStable::Stop),
+            "reseed" => Ok(OnStable::Reseed),
+            name => Err(format!("Unknown action when stable: {}", name)),
+        }
+    }
+}
+
+// The world repeats every `period` generations from `generation` on, moved by `displacement`
+// cells each time. A period of 1 that doesn't move is a still life, or nothing alive at all
+// when `extinct`, and one that moves is a spaceship.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stabilised {
+    pub generation: u64,
+    pub period: u64,
+    pub displacement: (i64, i64),
+    pub extinct: bool,
+}
+
+impl Stabilised {
+    // The speed in the usual notation: c/4 diagonal for a glider, c/2 orthogonal for
+    // a lightweight spaceship, and (2,1)c/6 for a knightship.
+    pub fn speed(&self) -> String {
+        let (dx, dy) = (self.displacement.0.abs(), self.displacement.1.abs());
+
+        let fraction = |distance: i64| {
+            let divisor = gcd(distance as u64, self.period);
+            let (distance, period) = (distance as u64 / divisor, self.period / divisor);
+
+            if distance == 1 {
+                format!("c/{}", period)
+            } else {
+                format!("{}c/{}", distance, period)
+            }
+        };
+
+        if dx == 0 || dy == 0 {
+            format!("{} orthogonal", fraction(dx.max(dy)))
+        } else if dx == dy {
+            format!("{} diagonal", fraction(dx))
+        } else {
+            format!("({},{})c/{}", dx.max(dy), dx.min(dy), self.period)
+        }
+    }
+}
+
+impl fmt::Display for Stabilised {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.extinct {
+            write!(f, "died out at generation {}", self.generation)
+        } else if self.displacement != (0, 0) {
+            write!(
+                f,
+                "moving from generation {} with period {}, displacement ({}, {}), speed {}",
+                self.generation,
+                self.period,
+                self.displacement.0,
+                self.displacement.1,
+                self.speed()
+            )
+        } else {
+            write!(
+                f,
+                "stabilised at generation {} with period {}",
+                self.generation, self.period
+            )
+        }
+    }
+}
+
+// What's hashed of each generation: the exact state, and the shape of the alive and dying
+// cells, which is the same wherever they are, with where it is. The cells are kept to split
+// into objects, so a world whose objects each repeat, moving their own ways, is seen as repeating.
+struct State {
+    hash: u64,
+    population: u64,
+    shape: Option<(u64, (i64, i64))>,
+    cells: Vec<(i64, i64, u32)>,
+}
+
+impl State {
+    // Gets the cells of the world once, alive ones with state 0 and dying ones with their decay.
+    fn of(game: &dyn Simulation) -> State {
+        let cells: Vec<(i64, i64, u32)> = game
+            .alive_positions()
+            .into_iter()
+            .map(|(x, y)| (x, y, 0))
+            .chain(
+                game.grid()
+                    .cells
+                    .iter()
+                    .flatten()
+                    .filter(|cell| cell.dying)
+                    .map(|cell| (cell.x as i64, cell.y as i64, cell.decay)),
+            )
+            .collect();
+
+        let alive = cells.iter().filter(|&&(_, _, state)| state == 0);
+        let population = alive.clone().count() as u64;
+        let corner =
+            viewport::bounds(alive.map(|&(x, y, _)| (x, y))).map(|(left, top, _, _)| (left, top));
+
+        State {
+            hash: shape_hash(&cells, (0, 0)),
+            population,
+            shape: corner.map(|corner| (shape_hash(&cells, corner), corner)),
+            cells,
+        }
+    }
+
+    // The shape and top left corner of each object, ordered by shape then place.
+    fn objects(&self) -> Vec<(u64, (i64, i64))> {
+        let states: HashMap<(i64, i64), u32> = self
+            .cells
+            .iter()
+            .map(|&(x, y, state)| ((x, y), state))
+            .collect();
+
+        let mut objects: Vec<(u64, (i64, i64))> =
+            objects::label(states.keys().copied(), Connectivity::MooreGap(1))
+                .into_iter()
+                .map(|object| {
+                    let (left, top, _, _) = object.bounds;
+                    let cells: Vec<(i64, i64, u32)> = object
+                        .cells
+                        .iter()
+                        .map(|&(x, y)| (x, y, states[&(x, y)]))
+                        .collect();
+
+                    (shape_hash(&cells, (left, top)), (left, top))
+                })
+                .collect();
+
+        objects.sort_unstable();
+        objects
+    }
+}
+
+// A hash of the shape of each object, wherever they are.
+fn objects_hash(objects: &[(u64, (i64, i64))]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for (shape, _) in objects.iter() {
+        shape.hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+// How many generations back a world is remembered, so the longest period found.
+// Older generations are forgotten, keeping the memory used bounded on long runs.
+const MAX_PERIOD: usize = 4096;
+
+// How often worlds are split into objects, in generations. Splitting a large world is slow,
+// so it's done only now and then, with the period found then narrowed down by `observe`.
+const OBJECTS_EVERY: u64 = 16;
+
+// A repeat found by `record`, from when the world was first seen as it is now.
+// How far it has moved isn't known when only its objects are the same.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Repeat {
+    first: u64,
+    displacement: Option<(i64, i64)>,
+}
+
+// Finds when a world repeats, either exactly or moved somewhere else,
+// and with `with_objects`, as objects which each repeat on their own.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CycleDetector {
+    seen: HashMap<u64, u64>,
+    shapes: HashMap<u64, (u64, (i64, i64))>,
+    objects: Option<HashMap<u64, u64>>,
+    history: VecDeque<(u64, u64, Option<u64>, Option<u64>)>,
+    stabilised: Option<Stabilised>,
+}
+
+impl CycleDetector {
+    pub fn new() -> CycleDetector {
+        CycleDetector::default()
+    }
+
+    // Also finds worlds whose objects each repeat, which is slower, so is only done
+    // when something is done once the world stabilises.
+    pub fn with_objects(self, objects: bool) -> CycleDetector {
+        CycleDetector {
+            objects: if objects { Some(HashMap::new()) } else { None },
+            ..self
+        }
+    }
+
+    // Forgets every generation seen, for a new world.
+    pub fn reset(&mut self) {
+        *self = CycleDetector::new().with_objects(self.objects.is_some());
+    }
+
+    // Returns when the world first repeats, and None before and after.
+    // A world whose objects are only the same as before is stepped on again, to check
+    // they keep repeating and find how far they move. Objects and engines stepping several
+    // generations at a time are only seen every so often, so the repeat found may be
+    // a multiple of the true period, which is then worked out by stepping on by each
+    // smaller period it could be.
+    pub fn observe(&mut self, generation: u64, game: &dyn Simulation) -> Option<Stabilised> {
+        let state = State::of(game);
+        let repeat = self.record(generation, &state)?;
+
+        let stabilised = Stabilised {
+            generation: repeat.first,
+            period: generation - repeat.first,
+            displacement: repeat.displacement.unwrap_or((0, 0)),
+            extinct: state.population == 0,
+        };
+
+        // Objects the same as before may have been different objects then,
+        // so the world is only known to repeat from now on.
+        let stabilised = match repeat.displacement {
+            Some(_) if game.generations_per_step() == 1 => return Some(stabilised),
+            Some(_) => stabilised,
+            None => Stabilised {
+                generation,
+                ..stabilised
+            },
+        };
+
+        self.stabilised = divisors(stabilised.period).into_iter().find_map(|period| {
+            let mut copy = game.clone_box();
+            copy.advance(period);
+
+            repeat_of(&state, &State::of(copy.as_ref())).map(|displacement| Stabilised {
+                period,
+                displacement,
+                ..stabilised
+            })
+        });
+
+        self.stabilised
+    }
+
+    // Seeing a state again at the same or an earlier generation, after stepping back,
+    // isn't a repeat. Objects are only looked at when the whole world doesn't repeat.
+    fn record(&mut self, generation: u64, state: &State) -> Option<Repeat> {
+        if self.stabilised.is_some() {
+            return None;
+        }
+
+        let repeat = match self.seen.get(&state.hash) {
+            Some(&first) if first < generation => Some(Repeat {
+                first,
+                displacement: Some((0, 0)),
+            }),
+            Some(_) => None,
+            None => {
+                self.seen.insert(state.hash, generation);
+                None
+            }
+        };
+
+        let repeat = repeat.or_else(|| {
+            let (shape, (x, y)) = state.shape?;
+
+            match self.shapes.get(&shape) {
+                Some(&(first, (first_x, first_y))) if first < generation => Some(Repeat {
+                    first,
+                    displacement: Some((x - first_x, y - first_y)),
+                }),
+                Some(_) => None,
+                None => {
+                    self.shapes.insert(shape, (generation, (x, y)));
+                    None
+                }
+            }
+        });
+
+        // An empty world is already found by its exact hash.
+        let mut objects = None;
+
+        let repeat = match self.objects {
+            Some(ref mut seen)
+                if repeat.is_none()
+                    && generation.is_multiple_of(OBJECTS_EVERY)
+                    && !state.cells.is_empty() =>
+            {
+                let hash = objects_hash(&state.objects());
+                objects = Some(hash);
+
+                match seen.get(&hash) {
+                    Some(&first) if first < generation => Some(Repeat {
+                        first,
+                        displacement: None,
+                    }),
+                    Some(_) => None,
+                    None => {
+                        seen.insert(hash, generation);
+                        None
+                    }
+                }
+            }
+            _ => repeat,
+        };
+
+        self.remember(generation, state, objects);
+
+        self.stabilised = repeat.map(|repeat| Stabilised {
+            generation: repeat.first,
+            period: generation - repeat.first,
+            displacement: repeat.displacement.unwrap_or((0, 0)),
+            extinct: state.population == 0,
+        });
+        repeat
+    }
+
+    // Keeps the last MAX_PERIOD generations, forgetting the hashes first seen before them.
+    fn remember(&mut self, generation: u64, state: &State, objects: Option<u64>) {
+        let shape = state.shape.map(|(shape, _)| shape);
+        self.history
+            .push_back((generation, state.hash, shape, objects));
+
+        while self.history.len() > MAX_PERIOD {
+            let (old, hash, shape, objects) = self.history.pop_front().unwrap();
+
+            if self.seen.get(&hash) == Some(&old) {
+                self.seen.remove(&hash);
+            }
+
+            if let Some(shape) = shape {
+                if self.shapes.get(&shape).map(|&(first, _)| first) == Some(old) {
+                    self.shapes.remove(&shape);
+                }
+            }
+
+            if let (Some(objects), Some(seen)) = (objects, self.objects.as_mut()) {
+                if seen.get(&objects) == Some(&old) {
+                    seen.remove(&objects);
+                }
+            }
+        }
+    }
+
+    pub fn stabilised(&self) -> Option<Stabilised> {
+        self.stabilised
+    }
+}
+
+// How far the world has moved between two states, if they're the same apart from that.
+// When only its objects are the same, that's how far the moving ones went, which must all
+// have gone the same way, as objects going different ways will meet or grow apart.
+fn repeat_of(first: &State, later: &State) -> Option<(i64, i64)> {
+    if later.hash == first.hash {
+        return Some((0, 0));
+    }
+
+    match (first.shape, later.shape) {
+        (Some((shape, (x, y))), Some((later_shape, (later_x, later_y))))
+            if shape == later_shape =>
+        {
+            return Some((later_x - x, later_y - y));
+        }
+        _ => (),
+    }
+
+    let (objects, later_objects) = (first.objects(), later.objects());
+
+    if objects_hash(&later_objects) != objects_hash(&objects) {
+        return None;
+    }
+
+    // Objects of the same shape keep their order as they move, as they all move alike.
+    let mut moves = objects
+        .iter()
+        .zip(later_objects.iter())
+        .map(|(&(_, (x, y)), &(_, (later_x, later_y)))| (later_x - x, later_y - y))
+        .filter(|&displacement| displacement != (0, 0));
+
+    let displacement = moves.next().unwrap_or((0, 0));
+
+    if moves.all(|other| other == displacement) {
+        Some(displacement)
+    } else {
+        None
+    }
+}
+
+// Every number dividing n, smallest first.
+fn divisors(n: u64) -> Vec<u64> {
+    let mut small = vec![];
+    let mut large = vec![];
+    let mut d = 1;
+
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            small.push(d);
+
+            if d * d != n {
+                large.push(n / d);
+            }
+        }
+
+        d += 1;
+    }
+
+    small.extend(large.into_iter().rev());
+    small
+}
+
+// A hash of (x, y, state) cells, measured from a corner, so the same wherever they are.
+// Each cell is hashed on its own and the hashes added, so the order they're in doesn't matter.
+fn shape_hash(cells: &[(i64, i64, u32)], (left, top): (i64, i64)) -> u64 {
+    cells.iter().fold(0, |sum: u64, &(x, y, state)| {
+        let mut hasher = DefaultHasher::new();
+        (x - left, y - top, state).hash(&mut hasher);
+
+        sum.wrapping_add(hasher.finish())
+    })
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::pattern::Pattern;
+    use crate::test_worlds::world;
+    use crate::world::World;
+
+    // Steps a world until it repeats, checking at most `limit` steps.
+    fn run(mut world: World, limit: u64) -> Option<Stabilised> {
+        let mut detector = CycleDetector::new().with_objects(true);
+
+        for step in 0..limit {
+            let generation = step * world.game.generations_per_step();
+
+            if let Some(stabilised) = detector.observe(generation, world.game.as_ref()) {
+                return Some(stabilised);
+            }
+
+            world.game.next();
+        }
+
+        None
+    }
+
+    /*
+
+       . . . . . .
+       . * * . . .
+       . * * . . .
+       . . . . . .
+
+    */
+    #[test]
+    fn still_life_has_period_one() {
+        let block = world("game_of_life", vec![(1, 1), (2, 1), (1, 2), (2, 2)]);
+
+        assert_eq!(
+            run(block, 10),
+            Some(Stabilised {
+                generation: 0,
+                period: 1,
+                displacement: (0, 0),
+                extinct: false
+            })
+        );
+    }
+
+    /*
+
+       . . . . . .      . . . . . .
+       . . . . . .      . . * . . .
+       . * * * . .  ->  . . * . . .
+       . . . . . .      . . * . . .
+
+    */
+    #[test]
+    fn blinker_has_period_two() {
+        let blinker = world("game_of_life", vec![(1, 2), (2, 2), (3, 2)]);
+
+        let stabilised = run(blinker, 10).unwrap();
+
+        assert_eq!((stabilised.generation, stabilised.period), (0, 2));
+        assert_eq!(
+            stabilised.to_string(),
+            "stabilised at generation 0 with period 2"
+        );
+    }
+
+    #[test]
+    fn extinction_is_reported() {
+        let lonely = world("game_of_life", vec![(1, 1), (4, 4)]);
+
+        let stabilised = run(lonely, 10).unwrap();
+
+        assert_eq!((stabilised.generation, stabilised.period), (1, 1));
+        assert_eq!(stabilised.to_string(), "died out at generation 1");
+    }
+
+    // Dying cells are part of the state, so Brian's Brain only repeats once they're gone.
+    #[test]
+    fn dying_cells_count_towards_the_state() {
+        let pair = world("brians_brain", vec![(2, 2), (3, 2)]);
+
+        let stabilised = run(pair, 50).unwrap();
+
+        assert!(stabilised.generation > 0);
+    }
+
+    #[test]
+    fn reports_only_once_and_ignores_stepping_back() {
+        let mut detector = CycleDetector::new();
+
+        let state = |hash| State {
+            hash,
+            population: 1,
+            shape: None,
+            cells: vec![],
+        };
+
+        assert_eq!(detector.record(0, &state(11)), None);
+        assert_eq!(detector.record(1, &state(22)), None);
+        assert_eq!(detector.record(1, &state(22)), None);
+        assert!(detector.record(2, &state(11)).is_some());
+        assert_eq!(detector.record(3, &state(22)), None);
+        assert_eq!(detector.stabilised().unwrap().period, 2);
+    }
+
+    /*
+
+       . * . . . . . . .
+       . . * . . . . . .
+       * * * . . . . . .
+       . . . . . . . . .
+
+    */
+    #[test]
+    fn glider_moves_diagonally_at_c_over_4() {
+        let grid = Grid::new_alive_grid(
+            9,
+            9,
+            String::new(),
+            String::new(),
+            String::new(),
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+            vec![],
+        );
+        let glider = World::from_ruleset("game_of_life", grid, 0).unwrap();
+
+        let stabilised = run(glider, 10).unwrap();
+
+        assert_eq!(stabilised.period, 4);
+        assert_eq!(stabilised.displacement, (1, 1));
+        assert_eq!(stabilised.speed(), "c/4 diagonal");
+        assert_eq!(
+            stabilised.to_string(),
+            "moving from generation 0 with period 4, displacement (1, 1), speed c/4 diagonal"
+        );
+    }
+
+    /*
+
+       . * . . *
+       * . . . .
+       * . . . *
+       * * * * .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn lightweight_spaceship_moves_orthogonally_at_c_over_2() {
+        let grid = Grid::new_alive_grid(
+            20, 8,
+            String::new(), String::new(), String::new(),
+            vec![
+                (11, 2),                   (14, 2),
+                (10, 3),
+                (10, 4),                   (14, 4),
+                (10, 5), (11, 5), (12, 5), (13, 5),
+            ],
+            vec![],
+        );
+        let lwss = World::unbounded("game_of_life", grid, 0).unwrap();
+
+        let stabilised = run(lwss, 10).unwrap();
+
+        assert_eq!(stabilised.period, 4);
+        assert_eq!(stabilised.displacement, (-2, 0));
+        assert_eq!(stabilised.speed(), "c/2 orthogonal");
+    }
+
+    // The glider and the block never look the same together again, but each repeats on its own.
+    #[test]
+    #[rustfmt::skip]
+    fn objects_repeating_apart_are_seen() {
+        let grid = Grid::new_alive_grid(
+            40, 40,
+            String::new(), String::new(), String::new(),
+            vec![
+                (1, 0), (2, 1), (0, 2), (1, 2), (2, 2),
+                (30, 30), (31, 30), (30, 31), (31, 31),
+            ],
+            vec![],
+        );
+        let glider_and_block = World::unbounded("game_of_life", grid, 0).unwrap();
+
+        let stabilised = run(glider_and_block, OBJECTS_EVERY + 1).unwrap();
+
+        assert_eq!(stabilised.generation, OBJECTS_EVERY);
+        assert_eq!(stabilised.period, 4);
+        assert_eq!(stabilised.displacement, (1, 1));
+    }
+
+    // The gliders look the same each period until they meet, but head opposite ways.
+    #[test]
+    #[rustfmt::skip]
+    fn gliders_on_a_collision_course_are_not_a_repeat() {
+        let grid = Grid::new_alive_grid(
+            40, 40,
+            String::new(), String::new(), String::new(),
+            vec![
+                (1, 0), (2, 1), (0, 2), (1, 2), (2, 2),
+                (21, 22), (20, 21), (22, 20), (21, 20), (20, 20),
+            ],
+            vec![],
+        );
+        let gliders = World::unbounded("game_of_life", grid, 0).unwrap();
+
+        // They're still apart at generation 28, having each moved 7 cells.
+        assert_eq!(run(gliders, 29), None);
+    }
+
+    // Only a detector asked to looks at objects.
+    #[test]
+    #[rustfmt::skip]
+    fn objects_are_only_looked_at_when_asked() {
+        let grid = Grid::new_alive_grid(
+            40, 40,
+            String::new(), String::new(), String::new(),
+            vec![
+                (1, 0), (2, 1), (0, 2), (1, 2), (2, 2),
+                (30, 30), (31, 30), (30, 31), (31, 31),
+            ],
+            vec![],
+        );
+        let mut world = World::unbounded("game_of_life", grid, 0).unwrap();
+        let mut detector = CycleDetector::new();
+
+        for generation in 0..=OBJECTS_EVERY * 2 {
+            assert_eq!(detector.observe(generation, world.game.as_ref()), None);
+            world.game.next();
+        }
+
+        detector.reset();
+        assert_eq!(detector.objects, None);
+    }
+
+    // Objects matching only in shape aren't a repeat until they keep matching.
+    #[test]
+    #[rustfmt::skip]
+    fn objects_matching_by_chance_are_not_a_repeat() {
+        let mut detector = CycleDetector::new().with_objects(true);
+        let grid = |alive_cells| Grid::new_alive_grid(
+            12, 12,
+            String::new(), String::new(), String::new(),
+            alive_cells,
+            vec![],
+        );
+
+        // A blinker and a block, then a block and a blinker close enough to meet.
+        let first = World::unbounded("game_of_life", grid(vec![(0, 1), (1, 1), (2, 1), (8, 8), (9, 8), (8, 9), (9, 9)]), 0).unwrap();
+        let later = World::unbounded("game_of_life", grid(vec![(0, 0), (1, 0), (0, 1), (1, 1), (0, 4), (1, 4), (2, 4)]), 0).unwrap();
+
+        assert_eq!(detector.observe(0, first.game.as_ref()), None);
+        assert_eq!(detector.observe(OBJECTS_EVERY, later.game.as_ref()), None);
+        assert_eq!(detector.stabilised(), None);
+    }
+
+    #[test]
+    fn long_gone_generations_are_forgotten() {
+        let mut detector = CycleDetector::new();
+
+        let state = |hash| State {
+            hash,
+            population: 1,
+            shape: None,
+            cells: vec![],
+        };
+
+        for generation in 0..MAX_PERIOD as u64 + 10 {
+            assert_eq!(detector.record(generation, &state(generation)), None);
+        }
+
+        assert_eq!(detector.seen.len(), MAX_PERIOD);
+        assert_eq!(detector.record(MAX_PERIOD as u64 + 10, &state(5)), None);
+        assert!(detector
+            .record(MAX_PERIOD as u64 + 11, &state(20))
+            .is_some());
+    }
+
+    // HashLife is only seen every 2^step_exponent generations, here 8 and 16,
+    // but periods are still given in single generations.
+    #[test]
+    fn hashlife_periods_are_in_generations() {
+        let hashlife = |alive_cells, step_exponent| {
+            let pattern = Pattern::new(6, 6, alive_cells);
+
+            World::hashlife("game_of_life", &pattern, Grid::new(1, 1), 0, step_exponent).unwrap()
+        };
+
+        let block = run(hashlife(vec![(1, 1), (2, 1), (1, 2), (2, 2)], 3), 10).unwrap();
+        assert_eq!((block.period, block.displacement), (1, (0, 0)));
+        assert_eq!(
+            block.to_string(),
+            "stabilised at generation 0 with period 1"
+        );
+
+        let blinker = run(hashlife(vec![(1, 2), (2, 2), (3, 2)], 3), 10).unwrap();
+        assert_eq!((blinker.period, blinker.displacement), (2, (0, 0)));
+
+        let glider = run(
+            hashlife(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], 4),
+            10,
+        )
+        .unwrap();
+        assert_eq!((glider.period, glider.displacement), (4, (1, 1)));
+        assert_eq!(glider.speed(), "c/4 diagonal");
+    }
+
+    #[test]
+    fn divisors_smallest_first() {
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(16), vec![1, 2, 4, 8, 16]);
+        assert_eq!(divisors(24), vec![1, 2, 3, 4, 6, 8, 12, 24]);
+    }
+
+    #[test]
+    fn speeds_in_standard_notation() {
+        let moving = |period, displacement| Stabilised {
+            generation: 0,
+            period,
+            displacement,
+            extinct: false,
+        };
+
+        assert_eq!(moving(5, (0, 2)).speed(), "2c/5 orthogonal");
+        assert_eq!(moving(7, (-1, 0)).speed(), "c/7 orthogonal");
+        assert_eq!(moving(6, (1, -2)).speed(), "(2,1)c/6");
+        assert_eq!(moving(12, (-3, -3)).speed(), "c/4 diagonal");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::example_worlds;
+use crate::grid::Grid;
+use crate::pattern::Pattern;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Brush {
+    Dead,
+    Alive,
+    Dying,
+}
+
+// A grid being drawn on, with a cursor and a preset chosen to paste at the cursor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Editor {
+    pub grid: Grid,
+    pub cursor: (u32, u32),
+    pub preset: usize,
+}
+
+impl Editor {
+    // Starts with the cursor in the middle of the grid.
+    pub fn new(grid: Grid) -> Editor {
+        let cursor = (grid.cells[0].len() as u32 / 2, grid.cells.len() as u32 / 2);
+
+        Editor {
+            grid,
+            cursor,
+            preset: 0,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.grid.cells[0].len() as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.grid.cells.len() as u32
+    }
+
+    // Moves the cursor, stopping at the edges of the grid.
+    pub fn move_cursor(&mut self, dx: i64, dy: i64) {
+        let (x, y) = self.cursor;
+
+        self.cursor = (
+            (x as i64 + dx).clamp(0, self.width() as i64 - 1) as u32,
+            (y as i64 + dy).clamp(0, self.height() as i64 - 1) as u32,
+        );
+    }
+
+    pub fn paint(&mut self, x: u32, y: u32, brush: Brush) {
+        if x >= self.width() || y >= self.height() {
+            return;
+        }
+
+        let cell = &self.grid.cells[y as usize][x as usize];
+
+        self.grid.cells[y as usize][x as usize] = match brush {
+            Brush::Dead => cell.set_dead(),
+            Brush::Alive => cell.set_alive(),
+            Brush::Dying => cell.set_dying(),
+        };
+    }
+
+    // Turns the cell under the cursor from dead to alive, alive to dying, and dying to dead.
+    pub fn cycle(&mut self) {
+        let (x, y) = self.cursor;
+        let cell = &self.grid.cells[y as usize][x as usize];
+
+        let brush = if cell.alive {
+            Brush::Dying
+        } else if cell.dying {
+            Brush::Dead
+        } else {
+            Brush::Alive
+        };
+
+        self.paint(x, y, brush);
+    }
+
+    pub fn clear(&mut self) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                self.paint(x, y, Brush::Dead);
+            }
+        }
+    }
+
+    pub fn preset_key(&self) -> &'static str {
+        example_worlds::KEYS[self.preset]
+    }
+
+    pub fn next_preset(&mut self) {
+        self.preset = (self.preset + 1) % example_worlds::KEYS.len();
+    }
+
+    // Pastes the chosen preset with its top left at the cursor, leaving out any cells
+    // that fall outside the grid. Dead cells of the preset don't replace what's drawn.
+    pub fn paste(&mut self) {
+        let preset = match example_worlds::find(self.preset_key()) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let (cx, cy) = self.cursor;
+
+        for &(x, y) in preset.alive_cells.iter() {
+            self.paint(cx + x, cy + y, Brush::Alive);
+        }
+
+        for &(x, y, _) in preset.dying_cells.iter() {
+            self.paint(cx + x, cy + y, Brush::Dying);
+        }
+    }
+
+    pub fn pattern(&self, rule: Option<String>) -> Pattern {
+        Pattern::from_grid(&self.grid, rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_stops_at_the_edges() {
+        let mut editor = Editor::new(Grid::new(5, 3));
+
+        assert_eq!(editor.cursor, (2, 1));
+
+        editor.move_cursor(-10, 1);
+        assert_eq!(editor.cursor, (0, 2));
+
+        editor.move_cursor(10, -10);
+        assert_eq!(editor.cursor, (4, 0));
+    }
+
+    #[test]
+    fn cycle_turns_a_cell_alive_then_dying_then_dead() {
+        let mut editor = Editor::new(Grid::new(3, 3));
+
+        editor.cycle();
+        assert!(editor.grid.cells[1][1].alive);
+
+        editor.cycle();
+        assert!(editor.grid.cells[1][1].dying);
+
+        editor.cycle();
+        assert_eq!(editor.grid, Grid::new(3, 3));
+    }
+
+    #[test]
+    fn paste_clips_the_preset_to_the_grid() {
+        let mut editor = Editor::new(Grid::new(10, 10));
+        editor.cursor = (0, 0);
+
+        editor.paste();
+
+        let preset = example_worlds::find("gosper").unwrap();
+        let expected: Vec<(u32, u32)> = preset
+            .alive_cells
+            .iter()
+            .filter(|&&(x, y)| x < 10 && y < 10)
+            .copied()
+            .collect();
+
+        let pattern = editor.pattern(None);
+
+        assert!(!pattern.alive_cells.is_empty());
+        assert!(pattern
+            .alive_cells
+            .iter()
+            .all(|cell| expected.contains(cell)));
+        assert!(expected
+            .iter()
+            .all(|cell| pattern.alive_cells.contains(cell)));
+    }
+
+    #[test]
+    fn paint_and_clear() {
+        let mut editor = Editor::new(Grid::new(4, 4));
+
+        editor.paint(0, 0, Brush::Alive);
+        editor.paint(3, 3, Brush::Dying);
+        editor.paint(9, 9, Brush::Alive);
+
+        let pattern = editor.pattern(Some(String::from("/2/3")));
+
+        assert_eq!(pattern.alive_cells, vec![(0, 0)]);
+        assert_eq!(pattern.dying_cells, vec![(3, 3, 1)]);
+        assert_eq!(pattern.rule, Some(String::from("/2/3")));
+
+        editor.clear();
+
+        assert_eq!(editor.grid, Grid::new(4, 4));
+    }
+}
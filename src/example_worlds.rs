@@ -1,60 +1,52 @@
-use crate::game_of_life::GameOfLife;
-use crate::grid::Grid;
-use crate::world::World;
+use crate::pattern::Pattern;
 
-pub fn find(key: &str) -> Option<World> {
+// The keys of every preset, in the order the editor offers them.
+pub const KEYS: &[&str] = &["gosper"];
+
+pub fn find(key: &str) -> Option<Pattern> {
     match key {
-        "gosper" => Some(World {
-            game: Box::new(GameOfLife {
-                grid: Grid::new_alive_grid(
-                    40,
-                    40,
-                    String::from("."),
-                    String::from("x"),
-                    String::from("#"),
-                    vec![
-                        (25, 1),
-                        (23, 2),
-                        (25, 2),
-                        (13, 3),
-                        (14, 3),
-                        (21, 3),
-                        (22, 3),
-                        (35, 3),
-                        (36, 3),
-                        (12, 4),
-                        (16, 4),
-                        (21, 4),
-                        (22, 4),
-                        (35, 4),
-                        (36, 3),
-                        (1, 5),
-                        (2, 5),
-                        (11, 5),
-                        (17, 5),
-                        (21, 5),
-                        (22, 5),
-                        (1, 6),
-                        (2, 6),
-                        (11, 6),
-                        (15, 6),
-                        (17, 6),
-                        (18, 6),
-                        (23, 6),
-                        (25, 6),
-                        (11, 7),
-                        (17, 7),
-                        (25, 7),
-                        (12, 8),
-                        (16, 8),
-                        (13, 9),
-                        (14, 9),
-                    ],
-                    vec![],
-                ),
-                seed: 0,
-            }),
-        }),
+        "gosper" => Some(Pattern::new(
+            40,
+            40,
+            vec![
+                (25, 1),
+                (23, 2),
+                (25, 2),
+                (13, 3),
+                (14, 3),
+                (21, 3),
+                (22, 3),
+                (35, 3),
+                (36, 3),
+                (12, 4),
+                (16, 4),
+                (21, 4),
+                (22, 4),
+                (35, 4),
+                (36, 3),
+                (1, 5),
+                (2, 5),
+                (11, 5),
+                (17, 5),
+                (21, 5),
+                (22, 5),
+                (1, 6),
+                (2, 6),
+                (11, 6),
+                (15, 6),
+                (17, 6),
+                (18, 6),
+                (23, 6),
+                (25, 6),
+                (11, 7),
+                (17, 7),
+                (25, 7),
+                (12, 8),
+                (16, 8),
+                (13, 9),
+                (14, 9),
+            ],
+        )),
         _ => None,
     }
 }
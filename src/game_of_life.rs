@@ -1,6 +1,8 @@
 use crate::cell::Cell;
 use crate::grid::Grid;
 use crate::neighbours::find_neighbours;
+use crate::parallel::map_rows;
+use crate::rule::Rule;
 use crate::world::Simulation;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -18,17 +20,24 @@ impl Simulation for GameOfLife {
         &self.grid
     }
 
+    fn rule(&self) -> String {
+        Rule::conway().to_string()
+    }
+
     fn next(&mut self) {
-        let width = self.grid.cells[0].len();
-        let height = self.grid.cells.len();
+        self.next_parallel(1)
+    }
 
-        let mut updated_cells: Vec<Vec<Cell>> = vec![];
+    fn next_parallel(&mut self, threads: usize) {
+        let grid = &self.grid;
+        let width = grid.cells[0].len();
+        let height = grid.cells.len();
 
-        for y in 0..height {
+        let updated_cells: Vec<Vec<Cell>> = map_rows(height, threads, |y| {
             let mut row: Vec<Cell> = vec![];
             for x in 0..width {
-                let cell = &self.grid.cells[y][x];
-                let neighbours = find_neighbours(&self.grid, cell);
+                let cell = &grid.cells[y][x];
+                let neighbours = find_neighbours(grid, cell);
 
                 if GameOfLife::is_alive(cell, neighbours) {
                     row.push(
@@ -39,7 +48,7 @@ impl Simulation for GameOfLife {
                             cell.dying_character.clone(),
                             cell.alive_character.clone(),
                         )
-                        .set_alive(),
+                        .set_alive_after(cell),
                     )
                 } else {
                     row.push(Cell::new_with_characters(
@@ -51,8 +60,8 @@ impl Simulation for GameOfLife {
                     ))
                 }
             }
-            updated_cells.push(row);
-        }
+            row
+        });
 
         self.grid.cells = updated_cells;
     }
@@ -63,11 +72,7 @@ impl GameOfLife {
         let alive_neighbours_count = neighbours.iter().filter(|&c| c.alive).count();
 
         if cell.alive {
-            if alive_neighbours_count < 2 || alive_neighbours_count > 3 {
-                false
-            } else {
-                true
-            }
+            (2..=3).contains(&alive_neighbours_count)
         } else {
             alive_neighbours_count == 3
         }
@@ -75,8 +80,11 @@ impl GameOfLife {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
+    use crate::test_worlds::{assert_parallel_matches_sequential, scattered_grid};
+    use crate::world::World;
 
     #[test]
     fn world_preserves_seed() {
@@ -352,4 +360,49 @@ mod tests {
 
         assert_eq!(is_alive, false);
     }
+
+    #[test]
+    fn parallel_step_matches_sequential_step() {
+        assert_parallel_matches_sequential(
+            World::from_ruleset("game_of_life", scattered_grid(), 0).unwrap(),
+        );
+    }
+
+    /*
+
+       . . . . .      . . . . .      . . . . .
+       . . 1 . .      . . . . .      . . 1 . .
+       . . 1 . .  ->  . 1 2 1 .  ->  . . 3 . .
+       . . 1 . .      . . . . .      . . 1 . .
+       . . . . .      . . . . .      . . . . .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn alive_cells_age_each_generation_they_survive() {
+        let grid = Grid::new_alive_grid(
+            5, 5,
+            String::new(), String::new(), String::new(),
+            vec![(2, 1), (2, 2), (2, 3)],
+            vec![],
+        );
+        let mut world = GameOfLife { grid, seed: 0 };
+
+        world.next();
+
+        assert_eq!(world.ages()[2], vec![0, 1, 2, 1, 0]);
+
+        world.next();
+
+        assert_eq!(
+            world.ages(),
+            vec![
+                vec![0, 0, 0, 0, 0],
+                vec![0, 0, 1, 0, 0],
+                vec![0, 0, 3, 0, 0],
+                vec![0, 0, 1, 0, 0],
+                vec![0, 0, 0, 0, 0],
+            ]
+        );
+    }
 }
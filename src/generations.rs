@@ -0,0 +1,170 @@
+use crate::cell::Cell;
+use crate::grid::Grid;
+use crate::neighbours::find_neighbours;
+use crate::parallel::map_rows;
+use crate::rule::GenerationsRule;
+use crate::world::Simulation;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Generations {
+    pub grid: Grid,
+    pub seed: u32,
+    pub rule: GenerationsRule,
+}
+
+impl Simulation for Generations {
+    fn seed(&self) -> &u32 {
+        &self.seed
+    }
+
+    fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn next(&mut self) {
+        self.next_parallel(1)
+    }
+
+    fn next_parallel(&mut self, threads: usize) {
+        self.grid.cells = Generations::next_cells(&self.grid, &self.rule, threads);
+    }
+}
+
+// A cell is born if it was dead and its alive neighbour count is in the birth list.
+// An alive cell survives if its alive neighbour count is in the survival list,
+// otherwise it starts dying, and each generation moves it one decay stage closer to dead.
+impl Generations {
+    pub fn next_cells(grid: &Grid, rule: &GenerationsRule, threads: usize) -> Vec<Vec<Cell>> {
+        let width = grid.cells[0].len();
+        let height = grid.cells.len();
+
+        map_rows(height, threads, |y| {
+            let mut row: Vec<Cell> = vec![];
+            for x in 0..width {
+                let cell = &grid.cells[y][x];
+                let neighbours = find_neighbours(grid, cell);
+
+                let updated_cell = Cell::new_with_characters(
+                    x as u32,
+                    y as u32,
+                    cell.dead_character.clone(),
+                    cell.dying_character.clone(),
+                    cell.alive_character.clone(),
+                );
+
+                if Generations::is_alive(rule, cell, neighbours) {
+                    row.push(updated_cell.set_alive_after(cell))
+                } else {
+                    row.push(updated_cell.set_decaying(rule.next_decay(cell.alive, cell.decay)))
+                }
+            }
+            row
+        })
+    }
+
+    pub fn is_alive(rule: &GenerationsRule, cell: &Cell, neighbours: Vec<&Cell>) -> bool {
+        let alive_neighbours_count = neighbours.iter().filter(|&c| c.alive).count();
+
+        rule.is_alive(cell.alive, cell.dying, alive_neighbours_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_worlds::{assert_parallel_matches_sequential, scattered_grid};
+    use crate::world::World;
+
+    /*
+
+       . . . .      . . . .      . . . .      . . . .
+       . * . .      . 1 . .      . 2 . .      . . . .
+       . . . .  ->  . . . .  ->  . . . .  ->  . . . .
+       . . . .      . . . .      . . . .      . . . .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn alive_cell_decays_through_each_state() {
+        let grid = Grid::new_alive_grid(
+            4, 4,
+            String::new(), String::new(), String::new(),
+            vec![(1, 1)],
+            vec![],
+        );
+
+        let mut world = Generations { grid, seed: 0, rule: GenerationsRule::parse("345/2/4").unwrap() };
+
+        world.next();
+        assert_eq!(world.grid.cells[1][1].decay, 1);
+
+        world.next();
+        assert_eq!(world.grid.cells[1][1].decay, 2);
+        assert!(world.grid.cells[1][1].dying);
+
+        world.next();
+        assert_eq!(world.grid.cells[1][1].decay, 0);
+        assert!(!world.grid.cells[1][1].dying);
+    }
+
+    /*
+
+       . . . . .      . . . . .
+       . * * * .      . * * * .
+       . * * * .  ->  . * 1 * .
+       . * * * .      . * * * .
+       . . . . .      . . . . .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn alive_cells_survive_according_to_rule() {
+        let grid = Grid::new_alive_grid(
+            5, 5,
+            String::new(), String::new(), String::new(),
+            vec![
+                (1, 1), (2, 1), (3, 1),
+                (1, 2), (2, 2), (3, 2),
+                (1, 3), (2, 3), (3, 3),
+            ],
+            vec![],
+        );
+
+        let mut world = Generations { grid, seed: 0, rule: GenerationsRule::parse("345/2/4").unwrap() };
+
+        world.next();
+
+        let alive_in_block: Vec<(usize, usize)> = (1..4)
+            .flat_map(|y| (1..4).map(move |x| (x, y)))
+            .filter(|&(x, y)| world.grid.cells[y][x].alive)
+            .collect();
+
+        assert_eq!(alive_in_block, vec![(1, 1), (2, 1), (3, 1), (1, 2), (3, 2), (1, 3), (2, 3), (3, 3)]);
+        assert_eq!(world.grid.cells[2][2].decay, 1);
+    }
+
+    #[test]
+    fn dying_cell_is_not_born() {
+        let dying_cell = Cell::new(0, 0).set_dying();
+
+        let cell_one = &Cell::new(0, 1).set_alive();
+        let cell_two = &Cell::new(1, 0).set_alive();
+
+        let neighbours = vec![cell_one, cell_two];
+
+        let is_alive =
+            Generations::is_alive(&GenerationsRule::brians_brain(), &dying_cell, neighbours);
+        assert!(!is_alive);
+    }
+
+    #[test]
+    fn parallel_step_matches_sequential_step() {
+        assert_parallel_matches_sequential(
+            World::from_ruleset("345/2/4", scattered_grid(), 0).unwrap(),
+        );
+    }
+}
@@ -1,13 +1,48 @@
 use itertools::Itertools;
 
 use crate::cell::Cell;
+use crate::colour::{age_stage, Colours};
+use crate::neighbours::Topology;
+use crate::renderer::Glyph;
+
+// How cells are drawn as text: one character followed by a space per cell,
+// 1 by 2 cells per character as half blocks, or 2 by 4 cells per character as braille.
+// Without colours, half blocks and braille only show which cells are alive.
+// Age is as text, but alive cells are shaded, and coloured, by how long they've been alive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderMode {
+    Text,
+    HalfBlock,
+    Braille,
+    Age,
+}
+
+// Blocks of cells from fewest to most alive, when zoomed out.
+const DENSITY_SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+// Alive cells from newly born to oldest, when drawn by age.
+const AGE_SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+impl RenderMode {
+    pub fn parse(name: &str) -> Result<RenderMode, String> {
+        match name {
+            "text" => Ok(RenderMode::Text),
+            "half_block" => Ok(RenderMode::HalfBlock),
+            "braille" => Ok(RenderMode::Braille),
+            "age" => Ok(RenderMode::Age),
+            name => Err(format!("Unknown render mode: {}", name)),
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Grid {
     pub cells: Vec<Vec<Cell>>,
+    pub topology: Topology,
 }
 
 impl Grid {
+    #[allow(dead_code)]
     pub fn new(width: u32, height: u32) -> Grid {
         let mut cells: Vec<Vec<Cell>> = vec![];
 
@@ -19,7 +54,10 @@ impl Grid {
             cells.push(row);
         }
 
-        Grid { cells }
+        Grid {
+            cells,
+            topology: Topology::Bounded,
+        }
     }
 
     // TODO: rename
@@ -56,9 +94,17 @@ impl Grid {
             cells.push(row);
         }
 
-        Grid { cells }
+        Grid {
+            cells,
+            topology: Topology::Bounded,
+        }
+    }
+
+    pub fn with_topology(self, topology: Topology) -> Grid {
+        Grid { topology, ..self }
     }
 
+    #[allow(unstable_name_collisions)]
     pub fn display(&self) -> String {
         self.cells
             .iter()
@@ -71,11 +117,167 @@ impl Grid {
             .intersperse("\n".to_string())
             .collect()
     }
+
+    pub fn display_as(&self, mode: RenderMode) -> String {
+        Itertools::intersperse(
+            self.glyphs(mode, None)
+                .iter()
+                .map(|line| line.iter().map(|glyph| glyph.c).collect::<String>()),
+            "\n".to_string(),
+        )
+        .collect()
+    }
+
+    // The lines of characters drawn for the grid, each coloured by the state of its cell.
+    // With colours, dying cells are shown in half blocks and braille too, as they can
+    // then be told apart from alive cells.
+    pub fn glyphs(&self, mode: RenderMode, colours: Option<&Colours>) -> Vec<Vec<Glyph>> {
+        let colour = |cell: &Cell| colours.map(|colours| colours.of(cell));
+        let shown = |cell: &Option<&Cell>| match cell {
+            Some(cell) => cell.alive || (colours.is_some() && cell.dying),
+            None => false,
+        };
+
+        match mode {
+            RenderMode::Text => self.display_cells(|cell| {
+                cell.display()
+                    .chars()
+                    .map(|c| Glyph::new(c, colour(cell)))
+                    .collect()
+            }),
+            RenderMode::Age => self.display_cells(|cell| {
+                if cell.alive {
+                    vec![Glyph::new(
+                        AGE_SHADES[age_stage(cell.age, AGE_SHADES.len())],
+                        colours.map(|colours| colours.by_age(cell)),
+                    )]
+                } else {
+                    cell.display()
+                        .chars()
+                        .map(|c| Glyph::new(c, colour(cell)))
+                        .collect()
+                }
+            }),
+            // Two shown cells of different colours are drawn as a top half block
+            // on a background of the bottom cell's colour.
+            RenderMode::HalfBlock => self.display_blocks(1, 2, |cells| {
+                let top = Some(cells[0]).filter(shown).flatten();
+                let bottom = Some(cells[1]).filter(shown).flatten();
+
+                match (top, bottom) {
+                    (Some(top), Some(bottom)) if colour(top) != colour(bottom) => Glyph {
+                        background: colour(bottom),
+                        ..Glyph::new('▀', colour(top))
+                    },
+                    (Some(top), Some(_)) => Glyph::new('█', colour(top)),
+                    (Some(top), None) => Glyph::new('▀', colour(top)),
+                    (None, Some(bottom)) => Glyph::new('▄', colour(bottom)),
+                    (None, None) => Glyph::plain(' '),
+                }
+            }),
+            // Braille dots 1 to 8 are numbered down the left column then the right,
+            // except that the bottom row comes last. A block takes the colour of its
+            // most alive cell.
+            RenderMode::Braille => self.display_blocks(2, 4, |cells| {
+                let dots = [0x01, 0x02, 0x04, 0x40, 0x08, 0x10, 0x20, 0x80];
+                let bits = (0..8)
+                    .filter(|&i| shown(&cells[(i % 4) * 2 + i / 4]))
+                    .fold(0, |acc, i| acc | dots[i]);
+
+                let brightest = cells
+                    .iter()
+                    .filter(|cell| shown(cell))
+                    .flatten()
+                    .min_by_key(|cell| if cell.alive { 0 } else { cell.decay });
+
+                Glyph::new(
+                    char::from_u32(0x2800 + bits).unwrap(),
+                    brightest.and_then(|cell| colour(cell)),
+                )
+            }),
+        }
+    }
+
+    // Draws each zoom by zoom block of cells as one character followed by a space, shaded by
+    // how many of the cells are alive, and coloured as the most alive cell in the block.
+    pub fn density_glyphs(&self, zoom: usize, colours: Option<&Colours>) -> Vec<Vec<Glyph>> {
+        let shades = DENSITY_SHADES.len() - 1;
+
+        self.display_blocks(zoom, zoom, |cells| {
+            let alive = cells.iter().flatten().filter(|cell| cell.alive).count();
+            let shade = (alive * shades).div_ceil(cells.len());
+
+            let brightest = cells
+                .iter()
+                .flatten()
+                .filter(|cell| cell.alive || (colours.is_some() && cell.dying))
+                .min_by_key(|cell| if cell.alive { 0 } else { cell.decay });
+
+            Glyph::new(
+                DENSITY_SHADES[shade],
+                colours.and_then(|colours| brightest.map(|cell| colours.of(cell))),
+            )
+        })
+        .into_iter()
+        .map(|line| Itertools::intersperse(line.into_iter(), Glyph::plain(' ')).collect())
+        .collect()
+    }
+
+    // Draws each cell as its glyphs followed by a space.
+    fn display_cells<F>(&self, glyphs: F) -> Vec<Vec<Glyph>>
+    where
+        F: Fn(&Cell) -> Vec<Glyph>,
+    {
+        self.cells
+            .iter()
+            .map(|row| {
+                let mut line = vec![];
+
+                for (i, cell) in row.iter().enumerate() {
+                    if i > 0 {
+                        line.push(Glyph::plain(' '));
+                    }
+
+                    line.extend(glyphs(cell));
+                }
+
+                line
+            })
+            .collect()
+    }
+
+    // Draws each block of width by height cells as one glyph, given the cells in the block
+    // row by row. Cells past the edges of the grid are None.
+    fn display_blocks<F>(&self, width: usize, height: usize, glyph: F) -> Vec<Vec<Glyph>>
+    where
+        F: Fn(&[Option<&Cell>]) -> Glyph,
+    {
+        let grid_height = self.cells.len();
+        let grid_width = self.cells.first().map_or(0, |row| row.len());
+
+        (0..grid_height)
+            .step_by(height)
+            .map(|y| {
+                (0..grid_width)
+                    .step_by(width)
+                    .map(|x| {
+                        let cells: Vec<Option<&Cell>> = (0..height)
+                            .flat_map(|dy| (0..width).map(move |dx| (x + dx, y + dy)))
+                            .map(|(cx, cy)| self.cells.get(cy).and_then(|row| row.get(cx)))
+                            .collect();
+
+                        glyph(&cells)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::colour::{Palette, Theme};
 
     #[test]
     fn empty_grid() {
@@ -316,4 +518,106 @@ x x x x x x x x x x"
 . . . . . . . . . ."
         );
     }
+
+    /*
+
+       * . *
+       * * .
+       . * *
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn display_half_blocks() {
+        let grid = Grid::new_alive_grid(
+            3, 3,
+            String::new(), String::new(), String::new(),
+            vec![
+                (0, 0),         (2, 0),
+                (0, 1), (1, 1),
+                        (1, 2), (2, 2),
+            ],
+            vec![],
+        );
+
+        assert_eq!(grid.display_as(RenderMode::HalfBlock), "█▄▀\n ▀▀");
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn display_braille() {
+        let grid = Grid::new_alive_grid(
+            3, 5,
+            String::new(), String::new(), String::new(),
+            vec![
+                (0, 0),
+                        (1, 1),
+                (0, 3), (1, 3), (2, 3),
+                (0, 4),
+            ],
+            vec![],
+        );
+
+        assert_eq!(grid.display_as(RenderMode::Braille), "⣑⡀\n⠁ ".replace(' ', "\u{2800}"));
+    }
+
+    /*
+
+       * x
+       x .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn coloured_half_blocks_show_dying_cells() {
+        let grid = Grid::new_alive_grid(
+            2, 2,
+            String::new(), String::new(), String::new(),
+            vec![(0, 0)],
+            vec![(1, 0), (0, 1)],
+        );
+
+        let theme = Theme::find("classic").unwrap();
+        let colours = Colours::new(&theme, Palette::TrueColour, 1);
+        let alive = Some(Palette::TrueColour.colour(theme.alive));
+        let dying = Some(Palette::TrueColour.colour(theme.dying_start));
+
+        assert_eq!(
+            grid.glyphs(RenderMode::HalfBlock, Some(&colours)),
+            vec![vec![
+                Glyph { c: '▀', foreground: alive, background: dying },
+                Glyph::new('▀', dying),
+            ]]
+        );
+        assert_eq!(grid.display_as(RenderMode::HalfBlock), "▀ ");
+    }
+
+    #[test]
+    fn display_by_age() {
+        let mut grid = Grid::new_alive_grid(
+            4,
+            1,
+            ".".to_owned(),
+            "x".to_owned(),
+            "*".to_owned(),
+            vec![(0, 0), (1, 0), (2, 0)],
+            vec![(3, 0)],
+        );
+
+        grid.cells[0][1].age = 3;
+        grid.cells[0][2].age = 40;
+
+        assert_eq!(grid.display_as(RenderMode::Age), "░ ▒ █ x");
+    }
+
+    #[test]
+    fn parse_render_modes() {
+        assert_eq!(RenderMode::parse("text"), Ok(RenderMode::Text));
+        assert_eq!(RenderMode::parse("half_block"), Ok(RenderMode::HalfBlock));
+        assert_eq!(RenderMode::parse("braille"), Ok(RenderMode::Braille));
+        assert_eq!(RenderMode::parse("age"), Ok(RenderMode::Age));
+        assert!(RenderMode::parse("ascii").is_err());
+        assert!(RenderMode::parse("textile").is_err());
+        assert!(RenderMode::parse("half").is_err());
+    }
 }
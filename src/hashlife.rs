@@ -0,0 +1,879 @@
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::grid::Grid;
+use crate::macrocell::{self, Macrocell};
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::viewport::{Bounds, Viewport};
+use crate::world::Simulation;
+
+type NodeId = usize;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+// Stores holding more nodes than this are rebuilt from the nodes still in use.
+const MAX_NODES: usize = 1 << 22;
+
+// A square of 2^level by 2^level cells, made of four quadrants one level down.
+// Level 0 nodes are single cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Node {
+    level: u8,
+    nw: NodeId,
+    ne: NodeId,
+    sw: NodeId,
+    se: NodeId,
+    population: u64,
+}
+
+// Every distinct node is stored once, so identical regions of the world share a node,
+// and the result of stepping a node is remembered for the next time it appears.
+#[derive(Clone, Debug)]
+struct NodeStore {
+    nodes: Vec<Node>,
+    ids: HashMap<(NodeId, NodeId, NodeId, NodeId), NodeId>,
+    empty: Vec<NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    rule: Rule,
+    max_nodes: usize,
+}
+
+impl NodeStore {
+    fn new(rule: Rule) -> NodeStore {
+        let cell = |population| Node {
+            level: 0,
+            nw: DEAD,
+            ne: DEAD,
+            sw: DEAD,
+            se: DEAD,
+            population,
+        };
+
+        NodeStore {
+            nodes: vec![cell(0), cell(1)],
+            ids: HashMap::new(),
+            empty: vec![DEAD],
+            results: HashMap::new(),
+            rule,
+            max_nodes: MAX_NODES,
+        }
+    }
+
+    // A new store holding only the nodes making up a root, with the root's id in it.
+    // Remembered results are left behind, as most refer to nodes no longer in use.
+    fn collect(&self, root: NodeId) -> (NodeStore, NodeId) {
+        let mut store = NodeStore {
+            max_nodes: self.max_nodes,
+            ..NodeStore::new(self.rule.clone())
+        };
+        let root = store.copy(self, root, &mut HashMap::new());
+
+        (store, root)
+    }
+
+    fn copy(
+        &mut self,
+        from: &NodeStore,
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if from.level(id) == 0 {
+            return id;
+        }
+
+        if let Some(&copy) = copied.get(&id) {
+            return copy;
+        }
+
+        let [nw, ne, sw, se] = from
+            .children(id)
+            .map(|child| self.copy(from, child, copied));
+        let copy = self.join(nw, ne, sw, se);
+
+        copied.insert(id, copy);
+
+        copy
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.nodes[id].level
+    }
+
+    fn population(&self, id: NodeId) -> u64 {
+        self.nodes[id].population
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        let node = &self.nodes[id];
+
+        [node.nw, node.ne, node.sw, node.se]
+    }
+
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        if let Some(&id) = self.ids.get(&(nw, ne, sw, se)) {
+            return id;
+        }
+
+        let node = Node {
+            level: self.level(nw) + 1,
+            nw,
+            ne,
+            sw,
+            se,
+            population: self.population(nw)
+                + self.population(ne)
+                + self.population(sw)
+                + self.population(se),
+        };
+
+        self.nodes.push(node);
+        self.ids.insert((nw, ne, sw, se), self.nodes.len() - 1);
+
+        self.nodes.len() - 1
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+
+        self.empty[level as usize]
+    }
+
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(id);
+
+        let nw_se = self.children(nw)[3];
+        let ne_sw = self.children(ne)[2];
+        let sw_ne = self.children(sw)[1];
+        let se_nw = self.children(se)[0];
+
+        self.join(nw_se, ne_sw, sw_ne, se_nw)
+    }
+
+    // The nine overlapping squares, one level down, that tile a node in a 3 by 3 arrangement.
+    fn overlapping(&mut self, id: NodeId) -> [NodeId; 9] {
+        let [nw, ne, sw, se] = self.children(id);
+        let [_, nw_ne, nw_sw, nw_se] = self.children(nw);
+        let [ne_nw, _, ne_sw, ne_se] = self.children(ne);
+        let [sw_nw, sw_ne, _, sw_se] = self.children(sw);
+        let [se_nw, se_ne, se_sw, _] = self.children(se);
+
+        [
+            nw,
+            self.join(nw_ne, ne_nw, nw_se, ne_sw),
+            ne,
+            self.join(nw_sw, nw_se, sw_nw, sw_ne),
+            self.join(nw_se, ne_sw, sw_ne, se_nw),
+            self.join(ne_sw, ne_se, se_nw, se_ne),
+            sw,
+            self.join(sw_ne, se_nw, sw_se, se_sw),
+            se,
+        ]
+    }
+
+    // The centre of a node, one level down, advanced 2^step_exponent generations.
+    // The step exponent can be at most level - 2.
+    fn step(&mut self, id: NodeId, step_exponent: u8) -> NodeId {
+        if let Some(&result) = self.results.get(&(id, step_exponent)) {
+            return result;
+        }
+
+        let level = self.level(id);
+
+        let result = if self.population(id) == 0 {
+            self.empty(level - 1)
+        } else if level == 2 {
+            self.step_four_by_four(id)
+        } else {
+            let squares = self.overlapping(id);
+
+            let advanced: Vec<NodeId> = if step_exponent == level - 2 {
+                squares
+                    .iter()
+                    .map(|&square| self.step(square, step_exponent - 1))
+                    .collect()
+            } else {
+                squares.iter().map(|&square| self.centre(square)).collect()
+            };
+
+            let remaining_exponent = if step_exponent == level - 2 {
+                step_exponent - 1
+            } else {
+                step_exponent
+            };
+
+            let quadrants = [
+                self.join(advanced[0], advanced[1], advanced[3], advanced[4]),
+                self.join(advanced[1], advanced[2], advanced[4], advanced[5]),
+                self.join(advanced[3], advanced[4], advanced[6], advanced[7]),
+                self.join(advanced[4], advanced[5], advanced[7], advanced[8]),
+            ];
+
+            let [nw, ne, sw, se] = [
+                self.step(quadrants[0], remaining_exponent),
+                self.step(quadrants[1], remaining_exponent),
+                self.step(quadrants[2], remaining_exponent),
+                self.step(quadrants[3], remaining_exponent),
+            ];
+
+            self.join(nw, ne, sw, se)
+        };
+
+        self.results.insert((id, step_exponent), result);
+
+        result
+    }
+
+    fn step_four_by_four(&mut self, id: NodeId) -> NodeId {
+        let quadrants = self.children(id);
+
+        let alive = |x: usize, y: usize| {
+            let quadrant = quadrants[(y / 2) * 2 + x / 2];
+            self.children(quadrant)[(y % 2) * 2 + x % 2] == ALIVE
+        };
+
+        let mut centre = [DEAD; 4];
+
+        for y in 1..3 {
+            for x in 1..3 {
+                let mut alive_neighbours_count = 0;
+
+                for ny in y - 1..=y + 1 {
+                    for nx in x - 1..=x + 1 {
+                        if (nx, ny) != (x, y) && alive(nx, ny) {
+                            alive_neighbours_count += 1;
+                        }
+                    }
+                }
+
+                if self.rule.is_alive(alive(x, y), alive_neighbours_count) {
+                    centre[(y - 1) * 2 + (x - 1)] = ALIVE;
+                }
+            }
+        }
+
+        self.join(centre[0], centre[1], centre[2], centre[3])
+    }
+
+    fn set_alive(&mut self, id: NodeId, x: u64, y: u64) -> NodeId {
+        let level = self.level(id);
+
+        if level == 0 {
+            return ALIVE;
+        }
+
+        let half = 1 << (level - 1);
+        let mut children = self.children(id);
+        let quadrant = (y / half) as usize * 2 + (x / half) as usize;
+
+        children[quadrant] = self.set_alive(children[quadrant], x % half, y % half);
+
+        self.join(children[0], children[1], children[2], children[3])
+    }
+
+    // The box holding every alive cell of a node, measured from its top left corner.
+    // Nodes are often repeated, so each one's box is worked out once.
+    fn bounds(&self, id: NodeId, known: &mut HashMap<NodeId, Option<Bounds>>) -> Option<Bounds> {
+        if self.population(id) == 0 {
+            return None;
+        }
+
+        if self.level(id) == 0 {
+            return Some((0, 0, 0, 0));
+        }
+
+        if let Some(&bounds) = known.get(&id) {
+            return bounds;
+        }
+
+        let half = 1 << (self.level(id) - 1);
+
+        let bounds = self
+            .children(id)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &child)| {
+                let (x, y) = ((i as i64 % 2) * half, (i as i64 / 2) * half);
+
+                self.bounds(child, known)
+                    .map(|(left, top, right, bottom)| (left + x, top + y, right + x, bottom + y))
+            })
+            .reduce(|(l1, t1, r1, b1), (l2, t2, r2, b2)| {
+                (l1.min(l2), t1.min(t2), r1.max(r2), b1.max(b2))
+            });
+
+        known.insert(id, bounds);
+
+        bounds
+    }
+
+    fn alive_cells(
+        &self,
+        id: NodeId,
+        x: i64,
+        y: i64,
+        viewport: &Viewport,
+        cells: &mut HashSet<(i64, i64)>,
+    ) {
+        let size = 1i64 << self.level(id);
+
+        if self.population(id) == 0
+            || x >= viewport.x + viewport.width as i64
+            || y >= viewport.y + viewport.height as i64
+            || x + size <= viewport.x
+            || y + size <= viewport.y
+        {
+            return;
+        }
+
+        if size == 1 {
+            cells.insert((x, y));
+            return;
+        }
+
+        let half = size / 2;
+        let [nw, ne, sw, se] = self.children(id);
+
+        self.alive_cells(nw, x, y, viewport, cells);
+        self.alive_cells(ne, x + half, y, viewport, cells);
+        self.alive_cells(sw, x, y + half, viewport, cells);
+        self.alive_cells(se, x + half, y + half, viewport, cells);
+    }
+}
+
+// A Life-like world on an unbounded plane, stepped with Gosper's HashLife algorithm.
+// Each call to `next` advances 2^step_exponent generations.
+// `grid` holds the cells visible through the viewport.
+// Nodes are never changed once stored, so clones of a world share the same store,
+// until a store grown too large is swapped for one of only the nodes still in use.
+#[derive(Clone, Debug)]
+pub struct HashLife {
+    store: Rc<RefCell<NodeStore>>,
+    root: NodeId,
+    origin: (i64, i64),
+    pub generation: u64,
+    pub step_exponent: u8,
+    pub seed: u32,
+    pub viewport: Viewport,
+    pub grid: Grid,
+}
+
+impl Simulation for HashLife {
+    fn seed(&self) -> &u32 {
+        &self.seed
+    }
+
+    fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn rule(&self) -> String {
+        self.store.borrow().rule.to_string()
+    }
+
+    fn next(&mut self) {
+        self.step_pow2(self.step_exponent);
+        self.grid = self
+            .viewport
+            .render(&self.alive_cells(&self.viewport), &self.grid);
+    }
+
+    fn population(&self) -> u64 {
+        self.store.borrow().population(self.root)
+    }
+
+    fn tracks_age(&self) -> bool {
+        false
+    }
+
+    fn view(&self, viewport: &Viewport) -> Grid {
+        let mut shifted = viewport.clone();
+        shifted.pan(self.viewport.x, self.viewport.y);
+
+        shifted.render(&self.alive_cells(&shifted), &self.grid)
+    }
+
+    fn alive_positions(&self) -> Vec<(i64, i64)> {
+        self.alive_cells(&self.everything())
+            .into_iter()
+            .map(|(x, y)| (x - self.viewport.x, y - self.viewport.y))
+            .collect()
+    }
+
+    fn pattern(&self) -> Pattern {
+        Pattern::from_cells(
+            self.alive_cells(&self.everything()).into_iter().collect(),
+            Some(self.rule()),
+        )
+    }
+
+    fn generations_per_step(&self) -> u64 {
+        1 << self.step_exponent
+    }
+
+    // Steps by each power of two making up the number of generations.
+    fn advance(&mut self, generations: u64) {
+        for exponent in 0..64 {
+            if generations & (1 << exponent) != 0 {
+                self.step_pow2(exponent);
+            }
+        }
+
+        self.grid = self
+            .viewport
+            .render(&self.alive_cells(&self.viewport), &self.grid);
+    }
+}
+
+impl HashLife {
+    // Starts from the alive cells of a pattern, showing its middle through a viewport
+    // of at most max_width by max_height cells, using the characters of the template grid.
+    pub fn from_pattern(
+        pattern: &Pattern,
+        rule: Rule,
+        seed: u32,
+        step_exponent: u8,
+        template: Grid,
+        max_width: u32,
+        max_height: u32,
+    ) -> HashLife {
+        let mut store = NodeStore::new(rule);
+        let root = store.empty(3);
+
+        let mut world = HashLife::new(store, root, (-4, -4), seed, step_exponent, template);
+
+        for &(x, y) in pattern.alive_cells.iter() {
+            world.set_alive(x as i64, y as i64);
+        }
+
+        world.show_middle(
+            (pattern.width as i64, pattern.height as i64),
+            max_width,
+            max_height,
+        );
+
+        world
+    }
+
+    // Starts from the quadtree of a macrocell file, joining its nodes straight into the store,
+    // so patterns far too large to list cell by cell can be run. Only state 1 cells are alive.
+    // The pattern is placed and shown as `from_pattern` would show its cells.
+    pub fn from_macrocell(
+        tree: &Macrocell,
+        rule: Rule,
+        seed: u32,
+        step_exponent: u8,
+        template: Grid,
+        max_width: u32,
+        max_height: u32,
+    ) -> HashLife {
+        let mut store = NodeStore::new(rule);
+        let mut ids: Vec<NodeId> = Vec::with_capacity(tree.nodes.len());
+
+        for node in tree.nodes.iter() {
+            let id = match node {
+                macrocell::Node::Leaf(cells) => {
+                    let empty = store.empty(3);
+
+                    cells
+                        .iter()
+                        .fold(empty, |id, &(x, y)| store.set_alive(id, x as u64, y as u64))
+                }
+                macrocell::Node::States(states) => {
+                    let [nw, ne, sw, se] =
+                        states.map(|state| if state == 1 { ALIVE } else { DEAD });
+
+                    store.join(nw, ne, sw, se)
+                }
+                macrocell::Node::Branch(level, children) => {
+                    let empty = store.empty(level - 1);
+                    let [nw, ne, sw, se] = children.map(|child| match child {
+                        0 => empty,
+                        child => ids[child - 1],
+                    });
+
+                    store.join(nw, ne, sw, se)
+                }
+            };
+
+            ids.push(id);
+        }
+
+        let root = *ids.last().unwrap();
+        let (left, top, right, bottom) = store
+            .bounds(root, &mut HashMap::new())
+            .unwrap_or((0, 0, -1, -1));
+
+        let mut world = HashLife::new(store, root, (-left, -top), seed, step_exponent, template);
+
+        while world.store.borrow().level(world.root) < 3 {
+            world.expand();
+        }
+
+        world.show_middle((right - left + 1, bottom - top + 1), max_width, max_height);
+
+        world
+    }
+
+    fn new(
+        store: NodeStore,
+        root: NodeId,
+        origin: (i64, i64),
+        seed: u32,
+        step_exponent: u8,
+        template: Grid,
+    ) -> HashLife {
+        HashLife {
+            store: Rc::new(RefCell::new(store)),
+            root,
+            origin,
+            generation: 0,
+            step_exponent,
+            seed,
+            viewport: Viewport::new(0, 0, 0, 0),
+            grid: template,
+        }
+    }
+
+    // Shows the middle of a pattern of the given size, with its top left at (0, 0),
+    // through a viewport of at most max_width by max_height cells.
+    fn show_middle(&mut self, (width, height): (i64, i64), max_width: u32, max_height: u32) {
+        let shown_width = cmp::min(width, max_width as i64);
+        let shown_height = cmp::min(height, max_height as i64);
+
+        self.viewport = Viewport::new(
+            (width - shown_width) / 2,
+            (height - shown_height) / 2,
+            shown_width as u32,
+            shown_height as u32,
+        );
+        self.grid = self
+            .viewport
+            .render(&self.alive_cells(&self.viewport), &self.grid);
+    }
+
+    pub fn set_alive(&mut self, x: i64, y: i64) {
+        while !self.contains(x, y) {
+            self.expand();
+        }
+
+        let (origin_x, origin_y) = self.origin;
+
+        self.root = self.store.borrow_mut().set_alive(
+            self.root,
+            (x - origin_x) as u64,
+            (y - origin_y) as u64,
+        );
+    }
+
+    // Advances the world 2^step_exponent generations at once.
+    pub fn step_pow2(&mut self, step_exponent: u8) {
+        if self.store.borrow().nodes.len() > self.store.borrow().max_nodes {
+            self.collect_garbage();
+        }
+
+        while self.store.borrow().level(self.root) < step_exponent + 3 || !self.is_padded() {
+            self.expand();
+        }
+
+        let level = self.store.borrow().level(self.root);
+        let offset = 1i64 << (level - 2);
+
+        self.root = self.store.borrow_mut().step(self.root, step_exponent);
+        self.origin = (self.origin.0 + offset, self.origin.1 + offset);
+        self.generation += 1 << step_exponent;
+    }
+
+    // Moves the world to a new store of only the nodes it is made of. Clones of the world,
+    // such as those kept to step back to, keep the old store until they are dropped.
+    fn collect_garbage(&mut self) {
+        let (mut store, root) = self.store.borrow().collect(self.root);
+
+        // A world whose own nodes nearly fill the store would otherwise be collected every step.
+        if store.nodes.len() > store.max_nodes / 2 {
+            store.max_nodes *= 2;
+        }
+
+        self.store = Rc::new(RefCell::new(store));
+        self.root = root;
+    }
+
+    pub fn alive_cells(&self, viewport: &Viewport) -> HashSet<(i64, i64)> {
+        let mut cells = HashSet::new();
+
+        self.store.borrow().alive_cells(
+            self.root,
+            self.origin.0,
+            self.origin.1,
+            viewport,
+            &mut cells,
+        );
+
+        cells
+    }
+
+    // A viewport over the whole of the stored world.
+    fn everything(&self) -> Viewport {
+        let size = (1u64 << cmp::min(self.store.borrow().level(self.root), 32)).min(u32::MAX as u64)
+            as u32;
+
+        Viewport::new(self.origin.0, self.origin.1, size, size)
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let size = 1i64 << self.store.borrow().level(self.root);
+        let (origin_x, origin_y) = self.origin;
+
+        x >= origin_x && y >= origin_y && x < origin_x + size && y < origin_y + size
+    }
+
+    // Whether every alive cell lies within the middle quarter of the root.
+    fn is_padded(&mut self) -> bool {
+        let mut store = self.store.borrow_mut();
+        let centre = store.centre(self.root);
+        let middle = store.centre(centre);
+
+        store.population(middle) == store.population(self.root)
+    }
+
+    // Doubles the size of the root, keeping the current root in the middle.
+    fn expand(&mut self) {
+        let mut store = self.store.borrow_mut();
+        let level = store.level(self.root);
+        let e = store.empty(level - 1);
+        let [nw, ne, sw, se] = store.children(self.root);
+
+        let nw = store.join(e, e, e, nw);
+        let ne = store.join(e, e, ne, e);
+        let sw = store.join(e, sw, e, e);
+        let se = store.join(se, e, e, e);
+
+        let offset = 1i64 << (level - 1);
+
+        self.root = store.join(nw, ne, sw, se);
+        self.origin = (self.origin.0 - offset, self.origin.1 - offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sparse_life::SparseLife;
+
+    fn everything() -> Viewport {
+        Viewport::new(-1 << 31, -1 << 31, u32::MAX, u32::MAX)
+    }
+
+    // Starts from the alive cells of a grid, viewed through a viewport of the same size.
+    fn from_grid(grid: Grid, rule: Rule, seed: u32, step_exponent: u8) -> HashLife {
+        let pattern = Pattern::from_grid(&grid, None);
+
+        HashLife::from_pattern(
+            &pattern,
+            rule,
+            seed,
+            step_exponent,
+            grid,
+            pattern.width,
+            pattern.height,
+        )
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn glider_moves_one_cell_diagonally_every_four_generations() {
+        let grid = Grid::new_alive_grid(
+            4, 4,
+            String::new(), String::new(), String::new(),
+            vec![
+                        (1, 0),
+                                (2, 1),
+                (0, 2), (1, 2), (2, 2),
+            ],
+            vec![],
+        );
+
+        let mut world = from_grid(grid, Rule::conway(), 0, 10);
+
+        world.next();
+
+        let expected_cells: HashSet<(i64, i64)> = vec![
+                        (257, 256),
+                                    (258, 257),
+            (256, 258), (257, 258), (258, 258),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(world.generation, 1024);
+        assert_eq!(world.population(), 5);
+        assert_eq!(world.alive_cells(&everything()), expected_cells);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn single_generations_match_sparse_engine() {
+        let grid = Grid::new_alive_grid(
+            8, 8,
+            String::new(), String::new(), String::new(),
+            vec![
+                (1, 1), (2, 1), (5, 1),
+                (3, 2), (4, 2), (5, 2),
+                (1, 3), (2, 4), (6, 4),
+                (3, 5), (4, 5), (4, 6),
+            ],
+            vec![],
+        );
+
+        let mut hashlife = from_grid(grid.clone(), Rule::parse("B36/S23").unwrap(), 0, 0);
+        let mut sparse = SparseLife::from_grid(grid, Rule::parse("B36/S23").unwrap(), 0);
+
+        for _ in 0..50 {
+            hashlife.next();
+            sparse.next();
+
+            assert_eq!(hashlife.alive_cells(&everything()), sparse.alive_cells);
+            assert_eq!(hashlife.grid, sparse.grid);
+        }
+    }
+
+    #[test]
+    fn pattern_holds_every_alive_cell() {
+        let pattern = Pattern::new(3000, 1, vec![(0, 0), (1, 0), (2, 0), (2999, 0)]);
+        let template = Grid::new(1, 1);
+
+        let mut world = HashLife::from_pattern(&pattern, Rule::conway(), 0, 0, template, 10, 10);
+
+        assert_eq!(world.grid.cells.len(), 1);
+        assert_eq!(world.grid.cells[0].len(), 10);
+
+        world.next();
+
+        let mut expected = Pattern::new(1, 3, vec![(0, 0), (0, 1), (0, 2)]);
+        expected.rule = Some(String::from("B3/S23"));
+
+        assert_eq!(world.pattern(), expected);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn clone_keeps_its_generation_after_the_original_steps() {
+        let grid = Grid::new_alive_grid(
+            3, 3,
+            String::new(), String::new(), String::new(),
+            vec![(0, 1), (1, 1), (2, 1)],
+            vec![],
+        );
+
+        let mut world = from_grid(grid, Rule::conway(), 0, 0);
+        let clone = world.clone();
+
+        world.next();
+
+        assert_eq!(clone.generation, 0);
+        assert_ne!(clone.alive_cells(&everything()), world.alive_cells(&everything()));
+        assert_eq!(clone.population(), 3);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn blinker_is_unchanged_after_even_number_of_generations() {
+        let grid = Grid::new_alive_grid(
+            3, 3,
+            String::new(), String::new(), String::new(),
+            vec![(0, 1), (1, 1), (2, 1)],
+            vec![],
+        );
+
+        let mut world = from_grid(grid, Rule::conway(), 0, 0);
+        let starting_cells = world.alive_cells(&everything());
+
+        world.step_pow2(20);
+
+        assert_eq!(world.generation, 1 << 20);
+        assert_eq!(world.alive_cells(&everything()), starting_cells);
+    }
+
+    // A glider and a block, side by side in the south east quadrant of a level 5 root.
+    const GLIDER_AND_BLOCK: &str = "[M2] (golly 4.2)
+#R B3/S23
+.*$..*$***$
+$$$$$$......**$......**$
+4 1 2 0 0
+5 0 0 0 3";
+
+    #[test]
+    fn macrocell_nodes_give_the_same_world_as_their_cells() {
+        let tree = macrocell::parse_tree(GLIDER_AND_BLOCK).unwrap();
+        let pattern = macrocell::parse(GLIDER_AND_BLOCK).unwrap();
+        let template = Grid::new(1, 1);
+
+        let mut from_tree =
+            HashLife::from_macrocell(&tree, Rule::conway(), 0, 2, template.clone(), 10, 10);
+        let mut from_cells =
+            HashLife::from_pattern(&pattern, Rule::conway(), 0, 2, template, 10, 10);
+
+        assert_eq!(from_tree.viewport, from_cells.viewport);
+        assert_eq!(from_tree.grid, from_cells.grid);
+
+        for _ in 0..5 {
+            from_tree.next();
+            from_cells.next();
+
+            assert_eq!(from_tree.pattern(), from_cells.pattern());
+        }
+
+        assert_eq!(from_tree.population(), 9);
+    }
+
+    #[test]
+    fn small_macrocell_trees_are_grown_to_be_stepped() {
+        let tree = macrocell::parse_tree("[M2]\n1 1 1 1 1").unwrap();
+
+        let mut world =
+            HashLife::from_macrocell(&tree, Rule::conway(), 0, 0, Grid::new(1, 1), 10, 10);
+        world.next();
+
+        assert_eq!(
+            world.pattern().alive_cells,
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn store_stays_bounded_over_many_steps() {
+        let grid = Grid::new_alive_grid(
+            4, 4,
+            String::new(), String::new(), String::new(),
+            vec![
+                        (1, 0),
+                                (2, 1),
+                (0, 2), (1, 2), (2, 2),
+            ],
+            vec![],
+        );
+
+        let mut unbounded = from_grid(grid.clone(), Rule::conway(), 0, 0);
+        let mut bounded = from_grid(grid, Rule::conway(), 0, 0);
+        bounded.store.borrow_mut().max_nodes = 1000;
+
+        for _ in 0..2000 {
+            unbounded.next();
+            bounded.next();
+
+            assert!(bounded.store.borrow().nodes.len() <= 2000);
+        }
+
+        assert!(unbounded.store.borrow().nodes.len() > 2000);
+        assert_eq!(bounded.alive_cells(&everything()), unbounded.alive_cells(&everything()));
+        assert_eq!(bounded.grid, unbounded.grid);
+    }
+}
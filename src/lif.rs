@@ -0,0 +1,235 @@
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+
+// Reads a pattern in Life 1.05 or Life 1.06 format, depending on its "#Life" header.
+// Both formats place cells relative to an origin, so the pattern is moved
+// to start at the top left of its bounding box.
+pub fn parse(contents: &str) -> Result<Pattern, String> {
+    match contents.lines().next().map(|line| line.trim()) {
+        Some("#Life 1.06") => parse_106(contents),
+        Some("#Life 1.05") => parse_105(contents),
+        _ => Err("Missing #Life 1.05 or #Life 1.06 header".to_owned()),
+    }
+}
+
+// Life 1.06 is a list of "x y" coordinates, one alive cell per line.
+fn parse_106(contents: &str) -> Result<Pattern, String> {
+    let mut cells: Vec<(i64, i64)> = vec![];
+
+    for line in contents.lines().skip(1) {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let coordinates: Vec<Result<i64, _>> = line.split_whitespace().map(str::parse).collect();
+
+        match coordinates.as_slice() {
+            [Ok(x), Ok(y)] => cells.push((*x, *y)),
+            _ => return Err(format!("Invalid Life 1.06 line: {}", line)),
+        }
+    }
+
+    Ok(Pattern::from_cells(cells, None))
+}
+
+// Life 1.05 is made of blocks, each starting "#P x y", followed by rows of '.' and '*'.
+// "#N" means Conway's rule and "#R" gives a rule in S/B notation.
+fn parse_105(contents: &str) -> Result<Pattern, String> {
+    let mut cells: Vec<(i64, i64)> = vec![];
+    let mut rule = None;
+    let mut origin = (0, 0);
+    let mut row = 0;
+
+    for line in contents.lines().skip(1) {
+        let line = line.trim();
+
+        if let Some(position) = line.strip_prefix("#P") {
+            let coordinates: Vec<Result<i64, _>> =
+                position.split_whitespace().map(str::parse).collect();
+
+            match coordinates.as_slice() {
+                [Ok(x), Ok(y)] => origin = (*x, *y),
+                _ => return Err(format!("Invalid Life 1.05 block: {}", line)),
+            }
+
+            row = 0;
+        } else if line.starts_with("#N") {
+            rule = Some("B3/S23".to_owned());
+        } else if let Some(r) = line.strip_prefix("#R") {
+            rule = Some(r.trim().to_owned());
+        } else if line.starts_with('#') {
+            continue;
+        } else {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '*' => cells.push((origin.0 + x as i64, origin.1 + row)),
+                    '.' => (),
+                    c => return Err(format!("Unexpected '{}' in Life 1.05 line: {}", c, line)),
+                }
+            }
+
+            row += 1;
+        }
+    }
+
+    Ok(Pattern::from_cells(cells, rule))
+}
+
+pub fn write_106(pattern: &Pattern) -> String {
+    let mut lines = vec!["#Life 1.06".to_owned()];
+
+    lines.extend(
+        pattern
+            .alive_cells
+            .iter()
+            .map(|&(x, y)| format!("{} {}", x, y)),
+    );
+
+    lines.join("\n") + "\n"
+}
+
+// Rules are written with "#R" in S/B notation when they are Life-like rules other than Conway's.
+// Other rules, such as Generations rules, have no way of being written.
+pub fn write_105(pattern: &Pattern) -> Result<String, String> {
+    let mut lines = vec!["#Life 1.05".to_owned()];
+
+    match pattern.rule.as_ref().map(|rule| (rule, Rule::parse(rule))) {
+        None => lines.push("#N".to_owned()),
+        Some((_, Ok(rule))) if rule == Rule::conway() => lines.push("#N".to_owned()),
+        Some((_, Ok(rule))) => lines.push(format!("#R {}", rule.survival_birth_notation())),
+        Some((rule, Err(_))) => {
+            return Err(format!(
+                "Rule {} can't be written in Life 1.05 format",
+                rule
+            ))
+        }
+    }
+
+    lines.push("#P 0 0".to_owned());
+
+    let mut rows = vec![vec!['.'; pattern.width as usize]; pattern.height as usize];
+
+    for &(x, y) in pattern.alive_cells.iter() {
+        rows[y as usize][x as usize] = '*';
+    }
+
+    lines.extend(rows.iter().map(|row| {
+        let row: String = row.iter().collect();
+        let row = row.trim_end_matches('.');
+
+        // Blank rows keep one dead cell rather than being left empty. An empty line reads
+        // as a blank row too, but some readers skip empty lines, which would move the rows below.
+        if row.is_empty() {
+            ".".to_owned()
+        } else {
+            row.to_owned()
+        }
+    }));
+
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_life_106_glider() {
+        let pattern = parse(
+            "#Life 1.06
+0 -1
+1 0
+-1 1
+0 1
+1 1",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.width, 3);
+        assert_eq!(pattern.height, 3);
+        assert_eq!(
+            pattern.alive_cells,
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn parse_life_105_blocks() {
+        let pattern = parse(
+            "#Life 1.05
+#D Two blinkers
+#R 23/36
+#P -1 -1
+***
+#P 3 1
+*
+*
+*",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.width, 5);
+        assert_eq!(pattern.height, 5);
+        assert_eq!(pattern.rule, Some(String::from("23/36")));
+        assert_eq!(
+            pattern.alive_cells,
+            vec![(0, 0), (1, 0), (2, 0), (4, 2), (4, 3), (4, 4)]
+        );
+    }
+
+    #[test]
+    fn write_then_parse_gives_same_cells() {
+        let mut pattern = Pattern::new(3, 3, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        pattern.rule = Some(String::from("B3/S23"));
+
+        assert_eq!(parse(&write_105(&pattern).unwrap()).unwrap(), pattern);
+
+        pattern.rule = None;
+        assert_eq!(parse(&write_106(&pattern)).unwrap(), pattern);
+    }
+
+    #[test]
+    fn write_life_105_with_rule() {
+        let mut pattern = Pattern::new(3, 2, vec![(0, 1), (2, 1)]);
+        pattern.rule = Some(String::from("B36/S23"));
+
+        assert_eq!(
+            write_105(&pattern),
+            Ok(String::from(
+                "#Life 1.05
+#R 23/36
+#P 0 0
+.
+*.*
+"
+            ))
+        );
+    }
+
+    #[test]
+    fn reject_writing_rules_that_are_not_life_like() {
+        let mut pattern = Pattern::new(1, 1, vec![(0, 0)]);
+        pattern.rule = Some(String::from("345/2/4"));
+
+        assert!(write_105(&pattern).is_err());
+
+        pattern.rule = Some(String::from("B3/S23"));
+        assert!(write_105(&pattern).unwrap().contains("#N"));
+    }
+
+    #[test]
+    fn empty_and_dead_lines_are_both_blank_rows() {
+        let dead = parse("#Life 1.05\n#P 0 0\n*\n.\n*").unwrap();
+        let empty = parse("#Life 1.05\n#P 0 0\n*\n\n*").unwrap();
+
+        assert_eq!(dead.alive_cells, vec![(0, 0), (0, 2)]);
+        assert_eq!(empty, dead);
+    }
+
+    #[test]
+    fn reject_missing_header() {
+        assert!(parse("0 0\n1 1").is_err());
+    }
+}
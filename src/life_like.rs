@@ -0,0 +1,163 @@
+use crate::cell::Cell;
+use crate::grid::Grid;
+use crate::neighbours::find_neighbours;
+use crate::parallel::map_rows;
+use crate::rule::Rule;
+use crate::world::Simulation;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LifeLike {
+    pub grid: Grid,
+    pub seed: u32,
+    pub rule: Rule,
+}
+
+impl Simulation for LifeLike {
+    fn seed(&self) -> &u32 {
+        &self.seed
+    }
+
+    fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn next(&mut self) {
+        self.next_parallel(1)
+    }
+
+    fn next_parallel(&mut self, threads: usize) {
+        let grid = &self.grid;
+        let width = grid.cells[0].len();
+        let height = grid.cells.len();
+
+        let updated_cells: Vec<Vec<Cell>> = map_rows(height, threads, |y| {
+            let mut row: Vec<Cell> = vec![];
+            for x in 0..width {
+                let cell = &grid.cells[y][x];
+                let neighbours = find_neighbours(grid, cell);
+
+                let updated_cell = Cell::new_with_characters(
+                    x as u32,
+                    y as u32,
+                    cell.dead_character.clone(),
+                    cell.dying_character.clone(),
+                    cell.alive_character.clone(),
+                );
+
+                if self.is_alive(cell, neighbours) {
+                    row.push(updated_cell.set_alive_after(cell))
+                } else {
+                    row.push(updated_cell)
+                }
+            }
+            row
+        });
+
+        self.grid.cells = updated_cells;
+    }
+}
+
+impl LifeLike {
+    pub fn is_alive(&self, cell: &Cell, neighbours: Vec<&Cell>) -> bool {
+        let alive_neighbours_count = neighbours.iter().filter(|&c| c.alive).count();
+
+        self.rule.is_alive(cell.alive, alive_neighbours_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_of_life::GameOfLife;
+    use crate::test_worlds::{assert_parallel_matches_sequential, scattered_grid};
+    use crate::world::World;
+
+    /*
+
+       . . . . .      . . . . .
+       . . . . .      . . * . .
+       . * * * .  ->  . . * . .
+       . . . . .      . . * . .
+       . . . . .      . . . . .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn conway_rule_matches_game_of_life() {
+        let grid = Grid::new_alive_grid(
+            5, 5,
+            String::new(), String::new(), String::new(),
+            vec![(1, 2), (2, 2), (3, 2)],
+            vec![],
+        );
+
+        let mut life_like = LifeLike { grid: grid.clone(), seed: 0, rule: Rule::conway() };
+        let mut game_of_life = GameOfLife { grid, seed: 0 };
+
+        life_like.next();
+        game_of_life.next();
+
+        assert_eq!(life_like.grid, game_of_life.grid);
+    }
+
+    /*
+
+       . . . .      . . . .
+       . * * .      . . . .
+       . . . .  ->  . . . .
+       . . . .      . . . .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn seeds_rule_kills_every_alive_cell() {
+        let grid = Grid::new_alive_grid(
+            4, 4,
+            String::new(), String::new(), String::new(),
+            vec![(1, 1), (2, 1)],
+            vec![],
+        );
+
+        let mut world = LifeLike { grid, seed: 0, rule: Rule::parse("B2/S").unwrap() };
+
+        let expected_grid = Grid::new_alive_grid(
+            4, 4,
+            String::new(), String::new(), String::new(),
+            vec![
+                (1, 0), (2, 0),
+                (1, 2), (2, 2),
+            ],
+            vec![],
+        );
+
+        world.next();
+
+        assert_eq!(world.grid, expected_grid);
+    }
+
+    #[test]
+    fn highlife_dead_cell_becomes_alive_for_six_alive_neighbours() {
+        let world = LifeLike {
+            grid: Grid::new(1, 1),
+            seed: 0,
+            rule: Rule::parse("B36/S23").unwrap(),
+        };
+
+        let dead_cell = Cell::new(1, 1);
+        let alive_cells: Vec<Cell> = (0..6).map(|x| Cell::new(x, 0).set_alive()).collect();
+        let neighbours = alive_cells.iter().collect();
+
+        assert!(world.is_alive(&dead_cell, neighbours));
+    }
+
+    #[test]
+    fn parallel_step_matches_sequential_step() {
+        assert_parallel_matches_sequential(
+            World::from_ruleset("B36/S23", scattered_grid(), 0).unwrap(),
+        );
+    }
+}
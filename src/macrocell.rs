@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+
+use crate::pattern::Pattern;
+
+// A node of a macrocell quadtree. Two-state files have 8 by 8 leaves at level 3,
+// while multi-state files have 2 by 2 leaves at level 1 holding the state of each cell.
+// Branches give their children by number, where 0 is an empty quadrant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Leaf(Vec<(u32, u32)>),
+    States([u32; 4]),
+    Branch(u8, [usize; 4]),
+}
+
+impl Node {
+    pub fn level(&self) -> u8 {
+        match self {
+            Node::Leaf(_) => 3,
+            Node::States(_) => 1,
+            Node::Branch(level, _) => *level,
+        }
+    }
+}
+
+// The nodes of a macrocell file, numbered from 1, each made only of nodes before it.
+// The last node is the whole pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Macrocell {
+    pub nodes: Vec<Node>,
+    pub rule: Option<String>,
+}
+
+// Reads the quadtree of a pattern in Golly's macrocell format: an "[M2]" header, '#' comment
+// lines ("#R" gives the rule), then one quadtree node per line, numbered from 1.
+// Leaves are rows of '.' and '*' each ending with '$', or "1 a b c d" for the states
+// of 2 by 2 cells. Other nodes are "level nw ne sw se", where 0 is an empty quadrant.
+pub fn parse_tree(contents: &str) -> Result<Macrocell, String> {
+    let mut lines = contents.lines().map(|line| line.trim());
+
+    match lines.next() {
+        Some(header) if header.starts_with("[M2]") => (),
+        _ => return Err("Missing [M2] macrocell header".to_owned()),
+    }
+
+    let mut rule = None;
+    let mut nodes: Vec<Node> = vec![];
+
+    for line in lines {
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(r) = comment.strip_prefix('R') {
+                rule = Some(r.trim().to_owned());
+            }
+            continue;
+        }
+
+        let node = if line.starts_with(&['.', '*', '$'][..]) {
+            parse_leaf(line)?
+        } else {
+            parse_branch(line, &nodes)?
+        };
+
+        nodes.push(node);
+    }
+
+    match nodes.last() {
+        None => Err("Missing macrocell nodes".to_owned()),
+        Some(root) if root.level() > 60 => Err("Macrocell pattern is too large".to_owned()),
+        Some(_) => Ok(Macrocell { nodes, rule }),
+    }
+}
+
+// Reads a pattern in macrocell format, expanding its quadtree into cells.
+// The pattern is moved to start at the top left of its bounding box, with its cells sorted by row.
+pub fn parse(contents: &str) -> Result<Pattern, String> {
+    let Macrocell { nodes, rule } = parse_tree(contents)?;
+    let root = nodes.len();
+
+    if nodes[root - 1].level() > 31 {
+        return Err("Macrocell pattern is too large".to_owned());
+    }
+
+    let mut alive_cells: Vec<(u32, u32)> = vec![];
+    let mut dying_cells: Vec<(u32, u32, u32)> = vec![];
+
+    cells(&nodes, root, 0, 0, &mut alive_cells, &mut dying_cells);
+
+    let min_x = alive_cells
+        .iter()
+        .map(|&(x, _)| x)
+        .chain(dying_cells.iter().map(|&(x, _, _)| x))
+        .min()
+        .unwrap_or(0);
+    let min_y = alive_cells
+        .iter()
+        .map(|&(_, y)| y)
+        .chain(dying_cells.iter().map(|&(_, y, _)| y))
+        .min()
+        .unwrap_or(0);
+
+    let mut alive_cells: Vec<(u32, u32)> = alive_cells
+        .iter()
+        .map(|&(x, y)| (x - min_x, y - min_y))
+        .collect();
+    let mut dying_cells: Vec<(u32, u32, u32)> = dying_cells
+        .iter()
+        .map(|&(x, y, decay)| (x - min_x, y - min_y, decay))
+        .collect();
+
+    alive_cells.sort_by_key(|&(x, y)| (y, x));
+    dying_cells.sort_by_key(|&(x, y, _)| (y, x));
+
+    let width = alive_cells
+        .iter()
+        .map(|&(x, _)| x + 1)
+        .chain(dying_cells.iter().map(|&(x, _, _)| x + 1))
+        .max()
+        .unwrap_or(0);
+    let height = alive_cells
+        .iter()
+        .map(|&(_, y)| y + 1)
+        .chain(dying_cells.iter().map(|&(_, y, _)| y + 1))
+        .max()
+        .unwrap_or(0);
+
+    Ok(Pattern {
+        width,
+        height,
+        alive_cells,
+        dying_cells,
+        rule,
+    })
+}
+
+fn parse_leaf(line: &str) -> Result<Node, String> {
+    let mut cells: Vec<(u32, u32)> = vec![];
+    let mut x = 0;
+    let mut y = 0;
+
+    for c in line.chars() {
+        match c {
+            '.' => x += 1,
+            // Rows may end with '$' past the last, but cells must be within the leaf.
+            '*' if x >= 8 || y >= 8 => {
+                return Err(format!("Macrocell leaf is larger than 8 by 8: {}", line))
+            }
+            '*' => {
+                cells.push((x, y));
+                x += 1;
+            }
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            c => return Err(format!("Unexpected '{}' in macrocell leaf: {}", c, line)),
+        }
+
+        if x > 8 || y > 8 {
+            return Err(format!("Macrocell leaf is larger than 8 by 8: {}", line));
+        }
+    }
+
+    Ok(Node::Leaf(cells))
+}
+
+fn parse_branch(line: &str, nodes: &[Node]) -> Result<Node, String> {
+    let numbers: Vec<Result<usize, _>> = line.split_whitespace().map(str::parse).collect();
+
+    let (level, children) = match numbers.as_slice() {
+        [Ok(level), Ok(nw), Ok(ne), Ok(sw), Ok(se)] => (*level, [*nw, *ne, *sw, *se]),
+        _ => return Err(format!("Invalid macrocell node: {}", line)),
+    };
+
+    if level == 1 {
+        if children.iter().any(|&state| state > 255) {
+            return Err(format!("Invalid cell state in macrocell node: {}", line));
+        }
+
+        return Ok(Node::States([
+            children[0] as u32,
+            children[1] as u32,
+            children[2] as u32,
+            children[3] as u32,
+        ]));
+    }
+
+    // Children must be earlier nodes, one level down.
+    let valid = level > 1
+        && level <= 64
+        && children.iter().all(|&child| {
+            child == 0 || (child <= nodes.len() && nodes[child - 1].level() as usize == level - 1)
+        });
+
+    if valid {
+        Ok(Node::Branch(level as u8, children))
+    } else {
+        Err(format!("Invalid macrocell node: {}", line))
+    }
+}
+
+fn cells(
+    nodes: &[Node],
+    id: usize,
+    x: u32,
+    y: u32,
+    alive_cells: &mut Vec<(u32, u32)>,
+    dying_cells: &mut Vec<(u32, u32, u32)>,
+) {
+    if id == 0 {
+        return;
+    }
+
+    match &nodes[id - 1] {
+        Node::Leaf(leaf_cells) => {
+            alive_cells.extend(leaf_cells.iter().map(|&(cx, cy)| (x + cx, y + cy)));
+        }
+        Node::States(states) => {
+            for (i, &state) in states.iter().enumerate() {
+                let (cx, cy) = (x + i as u32 % 2, y + i as u32 / 2);
+
+                match state {
+                    0 => (),
+                    1 => alive_cells.push((cx, cy)),
+                    state => dying_cells.push((cx, cy, state - 1)),
+                }
+            }
+        }
+        Node::Branch(level, children) => {
+            let half = 1 << (level - 1);
+
+            for (i, &child) in children.iter().enumerate() {
+                let (cx, cy) = (x + (i as u32 % 2) * half, y + (i as u32 / 2) * half);
+                cells(nodes, child, cx, cy, alive_cells, dying_cells);
+            }
+        }
+    }
+}
+
+// Writes a pattern in macrocell format, with its top left at the top left of the root node.
+// Patterns with dying cells are written with multi-state leaves.
+pub fn write(pattern: &Pattern) -> String {
+    let multi_state = !pattern.dying_cells.is_empty();
+
+    let mut states: Vec<(u32, u32, u32)> = pattern
+        .alive_cells
+        .iter()
+        .map(|&(x, y)| (x, y, 1))
+        .chain(
+            pattern
+                .dying_cells
+                .iter()
+                .map(|&(x, y, decay)| (x, y, decay + 1)),
+        )
+        .collect();
+
+    states.sort_by_key(|&(x, y, _)| (y, x));
+    states.dedup_by_key(|&mut (x, y, _)| (x, y));
+
+    let leaf_level = if multi_state { 1 } else { 3 };
+    let mut level = leaf_level;
+
+    while (1u64 << level) < pattern.width.max(pattern.height) as u64 {
+        level += 1;
+    }
+
+    let mut writer = Writer {
+        lines: vec![],
+        ids: HashMap::new(),
+        leaf_level,
+    };
+
+    let root = writer.node(&states, 0, 0, level);
+
+    let mut lines = vec!["[M2] (rust-game-of-life)".to_owned()];
+
+    if let Some(ref rule) = pattern.rule {
+        lines.push(format!("#R {}", rule));
+    }
+
+    // An empty pattern still needs a root node.
+    if root == 0 {
+        writer.lines.push(if multi_state {
+            "1 0 0 0 0".to_owned()
+        } else {
+            "$".to_owned()
+        });
+    }
+
+    lines.extend(writer.lines);
+
+    lines.join("\n") + "\n"
+}
+
+// Numbers each distinct node as it is written, so repeated parts of a pattern are written once.
+struct Writer {
+    lines: Vec<String>,
+    ids: HashMap<String, usize>,
+    leaf_level: u8,
+}
+
+impl Writer {
+    // Writes the node for the square of 2^level cells with its top left at (x, y),
+    // given the (x, y, state) of the cells inside it, returning its number or 0 if it is empty.
+    fn node(&mut self, cells: &[(u32, u32, u32)], x: u32, y: u32, level: u8) -> usize {
+        if cells.is_empty() {
+            return 0;
+        }
+
+        let line = if level == self.leaf_level {
+            self.leaf(cells, x, y)
+        } else {
+            let half = 1 << (level - 1);
+
+            let mut quadrants: [Vec<(u32, u32, u32)>; 4] = Default::default();
+
+            for &(cx, cy, state) in cells.iter() {
+                let quadrant = ((cy - y) / half) as usize * 2 + ((cx - x) / half) as usize;
+                quadrants[quadrant].push((cx, cy, state));
+            }
+
+            let children: Vec<String> = quadrants
+                .iter()
+                .enumerate()
+                .map(|(i, quadrant)| {
+                    let (qx, qy) = (x + (i as u32 % 2) * half, y + (i as u32 / 2) * half);
+                    self.node(quadrant, qx, qy, level - 1).to_string()
+                })
+                .collect();
+
+            format!("{} {}", level, children.join(" "))
+        };
+
+        if let Some(&id) = self.ids.get(&line) {
+            return id;
+        }
+
+        self.lines.push(line.clone());
+        self.ids.insert(line, self.lines.len());
+
+        self.lines.len()
+    }
+
+    fn leaf(&self, cells: &[(u32, u32, u32)], x: u32, y: u32) -> String {
+        if self.leaf_level == 1 {
+            let mut states = [0; 4];
+
+            for &(cx, cy, state) in cells.iter() {
+                states[((cy - y) * 2 + (cx - x)) as usize] = state;
+            }
+
+            return format!("1 {} {} {} {}", states[0], states[1], states[2], states[3]);
+        }
+
+        let mut rows = vec![vec!['.'; 8]; 8];
+
+        for &(cx, cy, _) in cells.iter() {
+            rows[(cy - y) as usize][(cx - x) as usize] = '*';
+        }
+
+        // Each row ends with '$', leaving out dead cells at the end of a row and empty rows at the end.
+        let mut leaf: String = rows
+            .iter()
+            .map(|row| {
+                let row: String = row.iter().collect();
+                row.trim_end_matches('.').to_owned() + "$"
+            })
+            .collect();
+
+        while leaf.ends_with("$$") {
+            leaf.pop();
+        }
+
+        leaf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_two_state_leaves() {
+        let pattern = parse(
+            "[M2] (golly 4.2)
+#R B3/S23
+$$$$$$$.......*$
+.*$..*$***$
+4 1 0 0 2",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.rule, Some(String::from("B3/S23")));
+        assert_eq!(pattern.width, 4);
+        assert_eq!(pattern.height, 4);
+        assert_eq!(
+            pattern.alive_cells,
+            vec![(0, 0), (2, 1), (3, 2), (1, 3), (2, 3), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn parse_multi_state_nodes() {
+        let pattern = parse(
+            "[M2] (golly 4.2)
+#R /2/3
+1 0 1 2 0
+2 0 0 1 1",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.width, 4);
+        assert_eq!(pattern.height, 2);
+        assert_eq!(pattern.alive_cells, vec![(1, 0), (3, 0)]);
+        assert_eq!(pattern.dying_cells, vec![(0, 1, 1), (2, 1, 1)]);
+    }
+
+    #[test]
+    fn write_shares_repeated_nodes() {
+        let blocks: Vec<(u32, u32)> = (0..4)
+            .flat_map(|i| vec![(i * 8, 0), (i * 8 + 1, 0), (i * 8, 1), (i * 8 + 1, 1)])
+            .collect();
+
+        let mc = write(&Pattern::new(32, 2, blocks));
+
+        assert_eq!(
+            mc,
+            "[M2] (rust-game-of-life)
+**$**$
+4 1 1 0 0
+5 2 2 0 0
+"
+        );
+    }
+
+    #[test]
+    fn write_then_parse_gives_same_pattern() {
+        let mut pattern = Pattern::new(20, 9, vec![(0, 0), (3, 2), (19, 8)]);
+        pattern.rule = Some(String::from("B36/S23"));
+
+        assert_eq!(parse(&write(&pattern)).unwrap(), pattern);
+
+        let mut pattern = Pattern::new(6, 7, vec![(0, 0), (5, 6)]);
+        pattern.dying_cells = vec![(3, 3, 2), (2, 4, 1)];
+        pattern.rule = Some(String::from("/2/4"));
+
+        assert_eq!(parse(&write(&pattern)).unwrap(), pattern);
+    }
+
+    #[test]
+    fn reject_invalid_macrocell() {
+        assert!(parse("x = 3, y = 3\nbo$ob!").is_err());
+        assert!(parse("[M2]\n**$**$\n4 1 2 0 0").is_err());
+        assert!(parse("[M2]\n**$**$\n5 1 0 0 0").is_err());
+        assert!(parse("[M2]\n..x$").is_err());
+    }
+
+    #[test]
+    fn reject_cells_outside_a_leaf() {
+        assert!(parse_tree("[M2]\n$$$$$$$$*\n").is_err());
+        assert!(parse_tree("[M2]\n........*$\n").is_err());
+        assert!(parse("[M2]\n$$$$$$$$*\n").is_err());
+        assert!(parse_tree("[M2]\n.......*$$$$$$$$\n").is_ok());
+    }
+}
@@ -2,103 +2,411 @@ use clap::Parser;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use std::cmp;
+use std::io;
 use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::{thread, time};
 
+mod bit_grid;
 mod brians_brain;
 mod cell;
 mod cli;
+mod colour;
+mod cycle;
+mod editor;
 mod example_worlds;
 mod game_of_life;
+mod generations;
 mod grid;
+mod hashlife;
+mod lif;
+mod life_like;
+mod macrocell;
 mod neighbours;
+mod objects;
+mod packed_life;
+mod parallel;
+mod pattern;
+mod plaintext;
+mod progress;
+mod renderer;
+mod rle;
+mod rule;
+mod search;
+mod sparse_life;
+mod stats;
+mod stop;
+#[cfg(test)]
+mod test_worlds;
+mod tui;
+mod viewport;
 mod world;
 
-use brians_brain::BriansBrain;
-use cli::Cli;
-use game_of_life::GameOfLife;
-use grid::Grid;
+use bit_grid::BitGrid;
+use cli::{Cli, Command, Options};
+use colour::Colours;
+use cycle::OnStable;
+use grid::RenderMode;
+use neighbours::Topology;
+use pattern::Pattern;
+use progress::Progress;
+use renderer::{Glyph, Renderer};
+use rule::GenerationsRule;
+use stats::StatsWriter;
+use stop::{Ending, StopConditions, BAD_ARGUMENTS, FAILED};
+use tui::Tui;
+use viewport::View;
 use world::World;
 
 /*
     memorable seeds:
     - 4045 (starting cells = 40)
+    - seeds of soups leaving rare objects: game_of_life search
 */
 fn main() {
     let args = Cli::parse();
 
-    let mut world: World = if let Some(ref key) = args.preset {
-        match example_worlds::find(key) {
-            Some(w) => w,
-            _ => {
-                eprintln!("Didn't find a match for preset: {}", key);
-                process::exit(1)
-            }
-        }
-    } else {
-        let seed = args
-            .seed
-            .unwrap_or_else(|| rand::thread_rng().gen_range(1, 10000));
+    let Options {
+        topology,
+        render_mode,
+        zoom,
+        on_stable,
+        generations,
+        timeout,
+        palette,
+        theme,
+    } = or_exit(args.options(), BAD_ARGUMENTS);
 
-        println!("seed = {}", seed);
+    let mut view = View::new(zoom, args.follow);
 
-        let width = args.width.unwrap_or(40);
-        let height = args.height.unwrap_or(40);
-        let num_starting_cells = args.num_starting_cells.unwrap_or(40);
+    let stop_conditions = StopConditions {
+        extinction: args.stop_on_extinction,
+        stabilisation: on_stable == OnStable::Stop,
+        population_above: args.stop_above,
+        population_below: args.stop_below,
+        timeout,
+    };
+
+    let width = args.width.unwrap_or(40);
+    let height = args.height.unwrap_or(40);
+    let num_starting_cells = args.num_starting_cells.unwrap_or(40);
 
-        let grid = Grid::new_alive_grid(
+    let soup = |seed| {
+        Pattern::new(
             width,
             height,
-            args.dead_char.unwrap_or_else(|| ".".to_owned()),
-            args.dying_char.unwrap_or_else(|| "x".to_owned()),
-            args.alive_char.unwrap_or_else(|| "#".to_owned()),
             starting_cells(seed, width, height, num_starting_cells),
-            vec![],
+        )
+    };
+
+    if let Some(Command::Search {
+        soups,
+        first_seed,
+        max_generations,
+        rare,
+    }) = args.command
+    {
+        let rule = or_exit(
+            world::life_like_rule(args.ruleset.as_deref().unwrap_or("game_of_life")),
+            BAD_ARGUMENTS,
         );
 
-        args.ruleset
-            .map(|r| match r {
-                rule if rule.starts_with("game_of") || rule.starts_with("gameof") => World {
-                    game: Box::new(GameOfLife {
-                        grid: grid.clone(),
-                        seed,
-                    }),
-                },
-                rule if rule.starts_with("brian") => World {
-                    game: Box::new(BriansBrain {
-                        grid: grid.clone(),
-                        seed,
-                    }),
-                },
-                rule => panic!("Unknown ruleset: {}", rule),
-            })
-            .unwrap_or(World {
-                game: Box::new(GameOfLife {
-                    grid: grid.clone(),
-                    seed,
-                }),
-            })
+        let first_seed = first_seed.unwrap_or(1);
+        let seeds = first_seed..first_seed.saturating_add(soups.unwrap_or(1000));
+
+        let census = search::search(
+            seeds,
+            &rule,
+            max_generations.unwrap_or(5000),
+            args.threads.unwrap_or(1),
+            soup,
+        );
+
+        println!("{}", census.report(rare.unwrap_or(3)));
+
+        return;
+    }
+
+    // HashLife builds its nodes straight from a macrocell file, without expanding it into cells.
+    let tree = match (args.engine.as_deref(), args.pattern_file.as_deref()) {
+        (Some("hashlife"), Some(path)) => or_exit(pattern::read_macrocell(path), FAILED),
+        _ => None,
     };
 
+    let (pattern, seed) = if let Some(ref tree) = tree {
+        // The cells are in the tree; the pattern only carries its rule.
+        let mut pattern = Pattern::new(0, 0, vec![]);
+        pattern.rule = tree.rule.clone();
+
+        (pattern, 0)
+    } else if let Some(ref path) = args.pattern_file {
+        (
+            or_exit(pattern::read(path), FAILED).centred(width, height),
+            0,
+        )
+    } else if let Some(ref key) = args.preset {
+        let preset = example_worlds::find(key)
+            .ok_or_else(|| format!("Didn't find a match for preset: {}", key));
+
+        (or_exit(preset, BAD_ARGUMENTS), 0)
+    } else if args.edit && args.seed.is_none() {
+        (Pattern::new(width, height, vec![]), 0)
+    } else {
+        let seed = args
+            .seed
+            .unwrap_or_else(|| rand::thread_rng().gen_range(1, 10000));
+
+        // Headless runs give the seed in their summary.
+        if !args.headless {
+            println!("seed = {}", seed);
+        }
+
+        (soup(seed), seed)
+    };
+
+    let dead_char = args.dead_char.clone().unwrap_or_else(|| ".".to_owned());
+    let dying_char = args.dying_char.clone().unwrap_or_else(|| "x".to_owned());
+    let alive_char = args.alive_char.clone().unwrap_or_else(|| "#".to_owned());
+
+    // A rule saved in a pattern file may end with a bounded grid size, e.g. B3/S23:T40,30
+    let ruleset = args
+        .ruleset
+        .clone()
+        .or_else(|| {
+            pattern
+                .rule
+                .as_ref()
+                .map(|rule| rule.split(':').next().unwrap().to_owned())
+        })
+        .unwrap_or_else(|| "game_of_life".to_owned());
+
+    let build_world = |pattern: &Pattern, seed: u32| match args.engine.as_deref() {
+        Some("packed") if topology != Topology::Bounded => {
+            Err("The packed engine only supports a bounded topology".to_owned())
+        }
+        Some("packed") => World::packed(
+            &ruleset,
+            BitGrid::from_pattern(pattern),
+            Pattern::new(1, 1, vec![]).to_grid(&dead_char, &dying_char, &alive_char),
+            seed,
+        ),
+        Some("hashlife") => World::hashlife(
+            &ruleset,
+            pattern,
+            Pattern::new(1, 1, vec![]).to_grid(&dead_char, &dying_char, &alive_char),
+            seed,
+            args.step_exponent.unwrap_or(0),
+        ),
+        engine => {
+            let grid = pattern
+                .to_grid(&dead_char, &dying_char, &alive_char)
+                .with_topology(topology);
+
+            match engine {
+                None | Some("dense") => World::from_ruleset(&ruleset, grid, seed),
+                Some("sparse") => World::unbounded(&ruleset, grid, seed),
+                Some(engine) => Err(format!("Unknown engine: {}", engine)),
+            }
+        }
+    };
+
+    let first_world = match tree {
+        Some(ref tree) => World::hashlife_from_macrocell(
+            &ruleset,
+            tree,
+            Pattern::new(1, 1, vec![]).to_grid(&dead_char, &dying_char, &alive_char),
+            seed,
+            args.step_exponent.unwrap_or(0),
+        ),
+        None => build_world(&pattern, seed),
+    };
+
+    // The ruleset, engine and topology given don't make a world.
+    let mut world = or_exit(first_world, BAD_ARGUMENTS);
+
+    if render_mode == RenderMode::Age && !world.game.tracks_age() {
+        exit(
+            "Drawing by age needs the dense engine, which tracks how long cells have been alive",
+            BAD_ARGUMENTS,
+        );
+    }
+
+    // Each decay stage of a Generations rule has its own colour.
+    let colours = palette.map(|palette| {
+        let decay_stages = GenerationsRule::parse(&world.game.rule()).map_or(1, |r| r.states - 2);
+
+        Colours::new(&theme, palette, decay_stages)
+    });
+
+    let threads = args.threads.unwrap_or(1);
+    let snapshot_file = args
+        .snapshot_file
+        .clone()
+        .unwrap_or_else(|| "snapshot.rle".to_owned());
     let gen_length = time::Duration::from_millis(args.gen_length.unwrap_or(250));
 
-    clear_screen();
+    let stats = or_exit(
+        args.stats_out
+            .as_deref()
+            .map(StatsWriter::create)
+            .transpose(),
+        FAILED,
+    );
+
+    // Rows below the world in the terminal UI, or above it otherwise, that aren't drawn on.
+    let reserved_rows = if args.interactive || args.edit { 4 } else { 3 };
+
+    if args.zoom.as_deref() == Some("auto") {
+        if let Some(bounds) = world.game.bounds() {
+            let (columns, rows) = view.screen(world.game.grid(), render_mode, reserved_rows);
+            view.fit(bounds, columns, rows, render_mode);
+        }
+    }
+
+    if args.interactive || args.edit {
+        let drawing_file = args
+            .drawing_file
+            .clone()
+            .unwrap_or_else(|| "drawing.rle".to_owned());
 
-    for i in 1..1000 {
-        if let Some(ref preset) = args.preset {
-            println!("{}: key = {}", i, preset)
+        // Reseeding starts a new random world, whatever the world started from.
+        let tui = Tui::new(world, gen_length, threads, build_world, soup)
+            .with_snapshots(args.snapshot_at.clone(), snapshot_file)
+            .with_render_mode(render_mode)
+            .with_colours(colours)
+            .with_view(view)
+            .with_on_stable(on_stable)
+            .with_stats(stats);
+
+        let mut tui = if args.edit {
+            tui.editing(drawing_file)
         } else {
-            println!("for help: --help");
-            println!("seed = {}; generation = {}", world.game.seed(), i)
+            tui.with_drawing_file(drawing_file)
+        };
+
+        or_exit(tui.run(), FAILED);
+
+        return;
+    }
+
+    let mut renderer = Renderer::new();
+    let mut stdout = io::stdout();
+
+    let mut progress =
+        Progress::new(args.snapshot_at.clone(), snapshot_file, stats).with_on_stable(on_stable);
+
+    // Steps are counted across every world when reseeding, which starts again from generation 0.
+    let last_step = generations.map(|g| stop::steps_for(g, world.game.generations_per_step()));
+
+    // Ctrl-C stops the run as a stop condition would, so the statistics file is closed.
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let registered = signal_hook::flag::register(signal_hook::consts::SIGINT, interrupted.clone());
+    or_finish_and_exit(&mut progress, registered.map_err(|e| e.to_string()), FAILED);
+
+    let started = time::Instant::now();
+    let mut ending = Ending::Generations;
+
+    for step in 0.. {
+        let generation = progress.generation;
+
+        let stabilised = progress.observe(&world);
+
+        let stopped = stop_conditions
+            .check(world.game.population(), stabilised, started.elapsed())
+            .or_else(|| Some(Ending::Interrupted).filter(|_| interrupted.load(Ordering::Relaxed)));
+
+        let mut header = if let Some(ref path) = args.pattern_file {
+            vec![format!(
+                "{}: file = {}; population = {}",
+                generation,
+                path,
+                world.game.population()
+            )]
+        } else if let Some(ref preset) = args.preset {
+            vec![format!(
+                "{}: key = {}; population = {}",
+                generation,
+                preset,
+                world.game.population()
+            )]
+        } else {
+            vec![
+                "for help: --help".to_owned(),
+                format!(
+                    "seed = {}; generation = {}; population = {}",
+                    world.game.seed(),
+                    generation,
+                    world.game.population()
+                ),
+            ]
+        };
+
+        if let (Some(stabilised), Some(status)) =
+            (progress.detector.stabilised(), header.last_mut())
+        {
+            status.push_str(&format!("; {}", stabilised));
         }
 
-        print!("{}", world.game.grid().display());
+        if let (Some(stopped), Some(status)) = (stopped, header.last_mut()) {
+            status.push_str(&format!("; {}", stopped));
+        }
 
-        thread::sleep(gen_length);
+        if !args.headless {
+            let (columns, rows) = view.screen(world.game.grid(), render_mode, reserved_rows);
+            view.track(world.game.as_ref(), columns, rows, render_mode);
 
-        clear_screen();
+            let mut lines: Vec<Vec<Glyph>> =
+                header.iter().map(|line| renderer::plain(line)).collect();
+            lines.extend(view.glyphs(
+                world.game.as_ref(),
+                columns,
+                rows,
+                render_mode,
+                colours.as_ref(),
+            ));
+
+            let rendered = renderer.render(&mut stdout, &lines, None);
+            or_finish_and_exit(&mut progress, rendered.map_err(|e| e.to_string()), FAILED);
+        }
 
-        world.game.next();
+        if let Err(e) = progress.save_snapshot(&world) {
+            eprintln!("{}", e);
+        }
+
+        let recorded = progress.record_stats(&world);
+        or_finish_and_exit(&mut progress, recorded, FAILED);
+
+        if stopped.is_some() || Some(step) == last_step {
+            // Headless runs print only where they finished.
+            if args.headless {
+                println!("{}", header.last().unwrap());
+            }
+
+            ending = stopped.unwrap_or(Ending::Generations);
+            break;
+        }
+
+        if !args.headless {
+            thread::sleep(gen_length);
+        }
+
+        if stabilised.is_some() && on_stable == OnStable::Reseed {
+            let seed = rand::thread_rng().gen_range(1, 10000);
+
+            world =
+                or_finish_and_exit(&mut progress, build_world(&soup(seed), seed), BAD_ARGUMENTS);
+
+            progress.restart();
+        } else {
+            progress.step(&mut world, threads);
+        }
+    }
+
+    or_exit(progress.finish(), FAILED);
+
+    if ending != Ending::Generations {
+        process::exit(ending.exit_code())
     }
 
     fn starting_cells(
@@ -131,6 +439,24 @@ fn main() {
     }
 }
 
-fn clear_screen() {
-    print!("\x1B[2J\x1B[1;1H")
+// Prints why the run can't go on and exits, with BAD_ARGUMENTS for options which don't
+// make sense, the same as clap's own errors, and FAILED for anything else.
+fn exit(message: &str, code: i32) -> ! {
+    eprintln!("{}", message);
+    process::exit(code)
+}
+
+fn or_exit<T>(result: Result<T, String>, code: i32) -> T {
+    result.unwrap_or_else(|e| exit(&e, code))
+}
+
+// Exiting doesn't drop the statistics, so their file is closed first.
+fn or_finish_and_exit<T>(progress: &mut Progress, result: Result<T, String>, code: i32) -> T {
+    result.unwrap_or_else(|e| {
+        if let Err(finishing) = progress.finish() {
+            eprintln!("{}", finishing);
+        }
+
+        exit(&e, code)
+    })
 }
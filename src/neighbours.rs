@@ -1,62 +1,77 @@
 use crate::cell::Cell;
 use crate::grid::Grid;
 
-pub fn find_neighbours<'a>(grid: &'a Grid, cell: &'a Cell) -> Vec<&'a Cell> {
-    let width = grid.cells[0].len();
-    let height = grid.cells.len();
-
-    let mut neighbours: Vec<&Cell> = vec![];
-
-    if (cell.x > 0) & (cell.y > 0) {
-        let left = (cell.x - 1) as usize;
-        let above = (cell.y - 1) as usize;
-
-        neighbours.push(&grid.cells[above][left]);
-    }
-
-    if cell.x > 0 {
-        let left = (cell.x - 1) as usize;
-
-        neighbours.push(&grid.cells[cell.y as usize][left]);
-    }
-
-    if (cell.x > 0) & (cell.y < (height - 1) as u32) {
-        let left = (cell.x - 1) as usize;
-        let below = (cell.y + 1) as usize;
-
-        neighbours.push(&grid.cells[below][left]);
-    }
-
-    if cell.y > 0 {
-        let above = (cell.y - 1) as usize;
+// How the edges of a grid are joined together.
+// Bounded grids have no neighbours past their edges.
+// A torus joins the left edge to the right edge and the top edge to the bottom edge.
+// A Klein bottle is a torus whose top and bottom edges are joined with a horizontal twist.
+// A cross-surface twists both pairs of edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Topology {
+    Bounded,
+    Torus,
+    KleinBottle,
+    CrossSurface,
+}
 
-        neighbours.push(&grid.cells[above][cell.x as usize]);
+impl Topology {
+    pub fn parse(name: &str) -> Result<Topology, String> {
+        match name {
+            "bounded" => Ok(Topology::Bounded),
+            "torus" => Ok(Topology::Torus),
+            "klein_bottle" => Ok(Topology::KleinBottle),
+            "cross_surface" => Ok(Topology::CrossSurface),
+            name => Err(format!("Unknown topology: {}", name)),
+        }
     }
 
-    if cell.y < (height - 1) as u32 {
-        let below = (cell.y + 1) as usize;
-
-        neighbours.push(&grid.cells[below][cell.x as usize]);
+    // Maps a position, which may be up to one cell past an edge,
+    // to the cell it refers to on this topology.
+    // Twisting both edges of a cross-surface takes a corner's diagonal neighbour
+    // back to the corner itself, so that neighbour is left out.
+    pub fn wrap(&self, x: i64, y: i64, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (width, height) = (width as i64, height as i64);
+        let past_x = x < 0 || x >= width;
+        let past_y = y < 0 || y >= height;
+
+        let (x, y) = match self {
+            Topology::Bounded if past_x || past_y => return None,
+            Topology::KleinBottle if past_y => (width - 1 - x, y),
+            Topology::CrossSurface if past_x && past_y => return None,
+            Topology::CrossSurface if past_y => (width - 1 - x, y),
+            Topology::CrossSurface if past_x => (x, height - 1 - y),
+            _ => (x, y),
+        };
+
+        Some((x.rem_euclid(width) as usize, y.rem_euclid(height) as usize))
     }
+}
 
-    if (cell.x < (width - 1) as u32) & (cell.y > 0) {
-        let right = (cell.x + 1) as usize;
-        let above = (cell.y - 1) as usize;
+// A cell is a neighbour once, even where two positions past the edges wrap to it,
+// as both of a cross-surface corner's neighbours past its edges do.
+pub fn find_neighbours<'a>(grid: &'a Grid, cell: &'a Cell) -> Vec<&'a Cell> {
+    let width = grid.cells[0].len();
+    let height = grid.cells.len();
 
-        neighbours.push(&grid.cells[above][right]);
-    }
+    let mut neighbours: Vec<&Cell> = vec![];
 
-    if cell.x < (width - 1) as u32 {
-        let right = (cell.x + 1) as usize;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
 
-        neighbours.push(&grid.cells[cell.y as usize][right]);
-    }
+            let x = cell.x as i64 + dx;
+            let y = cell.y as i64 + dy;
 
-    if (cell.x < (width - 1) as u32) & (cell.y < (height - 1) as u32) {
-        let right = (cell.x + 1) as usize;
-        let below = (cell.y + 1) as usize;
+            if let Some((x, y)) = grid.topology.wrap(x, y, width, height) {
+                let cell = &grid.cells[y][x];
 
-        neighbours.push(&grid.cells[below][right]);
+                if !neighbours.iter().any(|&n| std::ptr::eq(n, cell)) {
+                    neighbours.push(cell);
+                }
+            }
+        }
     }
 
     neighbours
@@ -299,4 +314,206 @@ mod tests {
 
         assert_eq!(actual_neighbours, expected_neightbours);
     }
+
+    /*
+
+       * . . .
+       . . . .
+       . . . .
+       . . . .
+
+    */
+    #[test]
+    fn find_neighbours_top_left_corner_of_torus() {
+        let grid = Grid::new(4, 4).with_topology(Topology::Torus);
+
+        let neighbours: Vec<(u32, u32)> = find_neighbours(&grid, &grid.cells[0][0])
+            .iter()
+            .map(|c| (c.x, c.y))
+            .collect();
+
+        assert_eq!(
+            neighbours,
+            vec![
+                (3, 3),
+                (3, 0),
+                (3, 1),
+                (0, 3),
+                (0, 1),
+                (1, 3),
+                (1, 0),
+                (1, 1)
+            ]
+        );
+    }
+
+    /*
+
+       . * . .
+       . . . .
+       . . . .
+       . . . .
+
+    */
+    #[test]
+    fn find_neighbours_above_edge_of_klein_bottle() {
+        let grid = Grid::new(4, 4).with_topology(Topology::KleinBottle);
+
+        let neighbours: Vec<(u32, u32)> = find_neighbours(&grid, &grid.cells[0][1])
+            .iter()
+            .map(|c| (c.x, c.y))
+            .collect();
+
+        assert_eq!(
+            neighbours,
+            vec![
+                (3, 3),
+                (0, 0),
+                (0, 1),
+                (2, 3),
+                (1, 1),
+                (1, 3),
+                (2, 0),
+                (2, 1)
+            ]
+        );
+    }
+
+    /*
+
+       . . . .
+       * . . .
+       . . . .
+       . . . .
+
+    */
+    #[test]
+    fn find_neighbours_left_edge_of_cross_surface() {
+        let grid = Grid::new(4, 4).with_topology(Topology::CrossSurface);
+
+        let neighbours: Vec<(u32, u32)> = find_neighbours(&grid, &grid.cells[1][0])
+            .iter()
+            .map(|c| (c.x, c.y))
+            .collect();
+
+        assert_eq!(
+            neighbours,
+            vec![
+                (3, 3),
+                (3, 2),
+                (3, 1),
+                (0, 0),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (1, 2)
+            ]
+        );
+    }
+
+    /*
+
+       * . . .
+       . . . .
+       . . . .
+       . . . .
+
+    */
+    #[test]
+    fn find_neighbours_top_left_corner_of_klein_bottle() {
+        let grid = Grid::new(4, 4).with_topology(Topology::KleinBottle);
+
+        let neighbours: Vec<(u32, u32)> = find_neighbours(&grid, &grid.cells[0][0])
+            .iter()
+            .map(|c| (c.x, c.y))
+            .collect();
+
+        assert_eq!(
+            neighbours,
+            vec![
+                (0, 3),
+                (3, 0),
+                (3, 1),
+                (3, 3),
+                (0, 1),
+                (2, 3),
+                (1, 0),
+                (1, 1)
+            ]
+        );
+    }
+
+    /*
+
+       * . . .
+       . . . .
+       . . . .
+       . . . .
+
+    */
+    #[test]
+    fn find_neighbours_top_left_corner_of_cross_surface() {
+        let grid = Grid::new(4, 4).with_topology(Topology::CrossSurface);
+
+        let neighbours: Vec<(u32, u32)> = find_neighbours(&grid, &grid.cells[0][0])
+            .iter()
+            .map(|c| (c.x, c.y))
+            .collect();
+
+        assert_eq!(
+            neighbours,
+            vec![(3, 3), (3, 2), (0, 1), (2, 3), (1, 0), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn corners_are_never_their_own_neighbour() {
+        for topology in [Topology::KleinBottle, Topology::CrossSurface] {
+            let grid = Grid::new(4, 4).with_topology(topology);
+
+            for &(x, y) in &[(0, 0), (3, 0), (0, 3), (3, 3)] {
+                let cell = &grid.cells[y][x];
+
+                assert!(find_neighbours(&grid, cell)
+                    .iter()
+                    .all(|c| (c.x, c.y) != (cell.x, cell.y)));
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_are_counted_once() {
+        for topology in [
+            Topology::Bounded,
+            Topology::Torus,
+            Topology::KleinBottle,
+            Topology::CrossSurface,
+        ] {
+            let grid = Grid::new(4, 4).with_topology(topology);
+
+            for cell in grid.cells.iter().flatten() {
+                let mut neighbours: Vec<(u32, u32)> = find_neighbours(&grid, cell)
+                    .iter()
+                    .map(|c| (c.x, c.y))
+                    .collect();
+                let count = neighbours.len();
+
+                neighbours.sort_unstable();
+                neighbours.dedup();
+
+                assert_eq!(neighbours.len(), count, "{:?} {:?}", topology, cell);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_topology_names() {
+        assert_eq!(Topology::parse("bounded"), Ok(Topology::Bounded));
+        assert_eq!(Topology::parse("torus"), Ok(Topology::Torus));
+        assert_eq!(Topology::parse("klein_bottle"), Ok(Topology::KleinBottle));
+        assert_eq!(Topology::parse("cross_surface"), Ok(Topology::CrossSurface));
+        assert!(Topology::parse("sphere").is_err());
+        assert!(Topology::parse("torusfoo").is_err());
+        assert!(Topology::parse("klein").is_err());
+    }
 }
@@ -0,0 +1,234 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::grid::Grid;
+use crate::neighbours::Topology;
+use crate::viewport::{self, Bounds};
+use crate::world::Simulation;
+
+// Which alive cells are part of the same object: those next to each other, including
+// diagonally, or with up to `gap` dead cells between them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Connectivity {
+    Moore,
+    MooreGap(u32),
+}
+
+impl Connectivity {
+    // How far apart, across or down, connected cells can be.
+    pub fn distance(&self) -> i64 {
+        match self {
+            Connectivity::Moore => 1,
+            Connectivity::MooreGap(gap) => *gap as i64 + 1,
+        }
+    }
+}
+
+// A group of connected alive cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Object {
+    pub cells: HashSet<(i64, i64)>,
+    pub bounds: Bounds,
+    pub population: u64,
+}
+
+impl Object {
+    fn new(cells: HashSet<(i64, i64)>) -> Object {
+        Object {
+            bounds: viewport::bounds(cells.iter().copied()).unwrap(),
+            population: cells.len() as u64,
+            cells,
+        }
+    }
+}
+
+// The objects among the alive cells of a grid. Cells next to each other across the edges
+// of a wrapped grid are part of the same object, whose bounds then reach both edges.
+pub fn label_grid(grid: &Grid, connectivity: Connectivity) -> Vec<Object> {
+    let (width, height) = (grid.cells[0].len(), grid.cells.len());
+
+    let cells = grid
+        .cells
+        .iter()
+        .flatten()
+        .filter(|cell| cell.alive)
+        .map(|cell| (cell.x as i64, cell.y as i64));
+
+    label_wrapped(cells, connectivity, |x, y| {
+        grid.topology
+            .wrap(x, y, width, height)
+            .map(|(x, y)| (x as i64, y as i64))
+    })
+}
+
+// The objects of a world of any engine, in the coordinates of `Simulation::alive_positions`.
+// Only dense engines have wrapped grids, whose cells are all in the grid.
+pub fn label_world(game: &dyn Simulation, connectivity: Connectivity) -> Vec<Object> {
+    match game.grid().topology {
+        Topology::Bounded => label(game.alive_positions(), connectivity),
+        _ => label_grid(game.grid(), connectivity),
+    }
+}
+
+// Splits alive cells into objects, ordered by their top, then left, edge.
+pub fn label<I>(cells: I, connectivity: Connectivity) -> Vec<Object>
+where
+    I: IntoIterator<Item = (i64, i64)>,
+{
+    label_wrapped(cells, connectivity, |x, y| Some((x, y)))
+}
+
+// `wrap` gives where a cell near another is, if anywhere.
+fn label_wrapped<I, F>(cells: I, connectivity: Connectivity, wrap: F) -> Vec<Object>
+where
+    I: IntoIterator<Item = (i64, i64)>,
+    F: Fn(i64, i64) -> Option<(i64, i64)>,
+{
+    let distance = connectivity.distance();
+
+    let mut unseen: HashSet<(i64, i64)> = cells.into_iter().collect();
+    let mut objects = vec![];
+
+    while let Some(&start) = unseen.iter().next() {
+        unseen.remove(&start);
+
+        let mut cells = HashSet::new();
+        let mut queue = VecDeque::from(vec![start]);
+
+        while let Some((x, y)) = queue.pop_front() {
+            cells.insert((x, y));
+
+            for dy in -distance..=distance {
+                for dx in -distance..=distance {
+                    match wrap(x + dx, y + dy) {
+                        Some(near) if unseen.remove(&near) => queue.push_back(near),
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        objects.push(Object::new(cells));
+    }
+
+    objects.sort_by_key(|object| {
+        let (left, top, right, bottom) = object.bounds;
+        (top, left, bottom, right, object.population)
+    });
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    /*
+
+       * * . . . . . .
+       * . . . . * . .
+       . . . . * . . .
+       . . . . . . . .
+       . . * . . . . *
+
+    */
+    #[rustfmt::skip]
+    fn grid() -> Grid {
+        Grid::new_alive_grid(
+            8, 5,
+            String::new(), String::new(), String::new(),
+            vec![
+                (0, 0), (1, 0),
+                (0, 1),                         (5, 1),
+                                        (4, 2),
+                                (2, 4),                         (7, 4),
+            ],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn moore_connects_diagonal_neighbours() {
+        let objects = label_grid(&grid(), Connectivity::Moore);
+
+        let summary: Vec<(Bounds, u64)> = objects
+            .iter()
+            .map(|object| (object.bounds, object.population))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ((0, 0, 1, 1), 3),
+                ((4, 1, 5, 2), 2),
+                ((2, 4, 2, 4), 1),
+                ((7, 4, 7, 4), 1)
+            ]
+        );
+        assert_eq!(
+            objects[1].cells,
+            vec![(5, 1), (4, 2)].into_iter().collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn gaps_join_objects_with_dead_cells_between() {
+        let populations = |connectivity| -> Vec<u64> {
+            label_grid(&grid(), connectivity)
+                .iter()
+                .map(|object| object.population)
+                .collect()
+        };
+
+        assert_eq!(populations(Connectivity::MooreGap(0)), vec![3, 2, 1, 1]);
+        assert_eq!(populations(Connectivity::MooreGap(1)), vec![3, 3, 1]);
+        assert_eq!(populations(Connectivity::MooreGap(2)), vec![7]);
+    }
+
+    #[test]
+    fn worlds_of_any_engine_are_labelled() {
+        let world = World::unbounded("game_of_life", grid(), 0).unwrap();
+
+        assert_eq!(
+            label_world(world.game.as_ref(), Connectivity::Moore),
+            label_grid(&grid(), Connectivity::Moore)
+        );
+        assert!(label(vec![], Connectivity::Moore).is_empty());
+    }
+
+    /*
+
+       * . . . *
+       . . . . .
+       . . * . .      on a torus, the four corners are one block
+       . . . . .
+       * . . . *
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn objects_join_across_wrapped_edges() {
+        let grid = |topology| Grid::new_alive_grid(
+            5, 5,
+            String::new(), String::new(), String::new(),
+            vec![(0, 0), (4, 0), (2, 2), (0, 4), (4, 4)],
+            vec![],
+        )
+        .with_topology(topology);
+
+        let populations = |topology| -> Vec<u64> {
+            label_grid(&grid(topology), Connectivity::Moore)
+                .iter()
+                .map(|object| object.population)
+                .collect()
+        };
+
+        assert_eq!(populations(Topology::Bounded), vec![1, 1, 1, 1, 1]);
+        assert_eq!(populations(Topology::Torus), vec![4, 1]);
+
+        let world = World::from_ruleset("game_of_life", grid(Topology::Torus), 0).unwrap();
+        let objects = label_world(world.game.as_ref(), Connectivity::Moore);
+
+        assert_eq!(objects[0].bounds, (0, 0, 4, 4));
+        assert_eq!(objects[0].population, 4);
+    }
+}
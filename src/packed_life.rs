@@ -0,0 +1,169 @@
+use std::cmp;
+
+use crate::bit_grid::BitGrid;
+use crate::grid::Grid;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::viewport::Viewport;
+use crate::world::Simulation;
+
+// A bounded Life-like world stored as a BitGrid.
+// `grid` holds the cells visible through the viewport.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackedLife {
+    pub bits: BitGrid,
+    pub rule: Rule,
+    pub seed: u32,
+    pub viewport: Viewport,
+    pub grid: Grid,
+}
+
+impl Simulation for PackedLife {
+    fn seed(&self) -> &u32 {
+        &self.seed
+    }
+
+    fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn next(&mut self) {
+        self.next_parallel(1)
+    }
+
+    fn next_parallel(&mut self, threads: usize) {
+        self.bits = self.bits.step(&self.rule, threads);
+        self.grid = self.render();
+    }
+
+    fn population(&self) -> u64 {
+        self.bits.population()
+    }
+
+    fn tracks_age(&self) -> bool {
+        false
+    }
+
+    fn view(&self, viewport: &Viewport) -> Grid {
+        let mut shifted = viewport.clone();
+        shifted.pan(self.viewport.x, self.viewport.y);
+
+        self.render_through(&shifted)
+    }
+
+    fn alive_positions(&self) -> Vec<(i64, i64)> {
+        let (width, height) = (self.bits.width, self.bits.height);
+
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.bits.get(x, y))
+            .map(|(x, y)| (x as i64 - self.viewport.x, y as i64 - self.viewport.y))
+            .collect()
+    }
+
+    fn pattern(&self) -> Pattern {
+        let mut pattern = self.bits.to_pattern();
+        pattern.rule = Some(self.rule());
+        pattern
+    }
+}
+
+impl PackedLife {
+    // Shows the middle of the world, through a viewport of at most
+    // max_width by max_height cells, using the characters of the template grid.
+    pub fn new(
+        bits: BitGrid,
+        rule: Rule,
+        seed: u32,
+        template: Grid,
+        max_width: u32,
+        max_height: u32,
+    ) -> PackedLife {
+        let width = cmp::min(bits.width as u32, max_width);
+        let height = cmp::min(bits.height as u32, max_height);
+
+        let viewport = Viewport::new(
+            (bits.width as i64 - width as i64) / 2,
+            (bits.height as i64 - height as i64) / 2,
+            width,
+            height,
+        );
+
+        let mut world = PackedLife {
+            bits,
+            rule,
+            seed,
+            viewport,
+            grid: template,
+        };
+
+        world.grid = world.render();
+
+        world
+    }
+
+    fn render(&self) -> Grid {
+        self.render_through(&self.viewport)
+    }
+
+    fn render_through(&self, viewport: &Viewport) -> Grid {
+        viewport.render_with(
+            |x, y| {
+                x >= 0
+                    && y >= 0
+                    && (x as usize) < self.bits.width
+                    && (y as usize) < self.bits.height
+                    && self.bits.get(x as usize, y as usize)
+            },
+            &self.grid,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+    use crate::test_worlds::{assert_parallel_matches_sequential, scattered_grid};
+    use crate::world::World;
+
+    /*
+
+       . . . . . .      . . . . . .
+       . . . . . .      . . . * . .
+       . . * * * .  ->  . . . * . .
+       . . . . . .      . . . * . .
+       . . . . . .      . . . . . .
+
+    */
+    #[test]
+    fn shows_middle_of_world_through_viewport() {
+        let bits = BitGrid::from_pattern(&Pattern::new(6, 5, vec![(2, 2), (3, 2), (4, 2)]));
+
+        let mut world = PackedLife::new(bits, Rule::conway(), 0, Grid::new(1, 1), 4, 3);
+
+        assert_eq!(world.viewport, Viewport::new(1, 1, 4, 3));
+
+        world.next();
+
+        assert_eq!(world.population(), 3);
+        assert_eq!(
+            world.grid.display(),
+            ". . * .
+. . * .
+. . * ."
+        );
+    }
+
+    #[test]
+    fn parallel_step_matches_sequential_step() {
+        let grid = scattered_grid();
+        let bits = BitGrid::from_pattern(&Pattern::from_grid(&grid, None));
+
+        assert_parallel_matches_sequential(World::packed("B3/S23", bits, grid, 0).unwrap());
+    }
+}
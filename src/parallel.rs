@@ -0,0 +1,55 @@
+use std::cmp;
+use std::thread;
+
+// Builds rows 0..height by calling `row` for each, splitting the rows into
+// contiguous bands, one per thread. The rows are returned in order, so the
+// result is the same for any number of threads.
+pub fn map_rows<T, F>(height: usize, threads: usize, row: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let threads = cmp::max(1, cmp::min(threads, height));
+
+    if threads == 1 {
+        return (0..height).map(row).collect();
+    }
+
+    let band_height = height.div_ceil(threads);
+    let row = &row;
+
+    thread::scope(|scope| {
+        let bands: Vec<_> = (0..height)
+            .step_by(band_height)
+            .map(|start| {
+                let end = cmp::min(start + band_height, height);
+
+                scope.spawn(move || (start..end).map(row).collect::<Vec<T>>())
+            })
+            .collect();
+
+        bands
+            .into_iter()
+            .flat_map(|band| band.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_returned_in_order_for_any_number_of_threads() {
+        let expected: Vec<usize> = (0..10).map(|y| y * y).collect();
+
+        for threads in 0..12 {
+            assert_eq!(map_rows(10, threads, |y| y * y), expected);
+        }
+    }
+
+    #[test]
+    fn no_rows() {
+        assert_eq!(map_rows(0, 4, |y| y), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,343 @@
+use std::cmp;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::grid::Grid;
+use crate::lif;
+use crate::macrocell::{self, Macrocell};
+use crate::plaintext;
+use crate::rle;
+
+// The cells a world starts from, independent of how the world is stored.
+// Dying cells are given as (x, y, decay stage), and the rule is the one
+// the pattern was saved with, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub width: u32,
+    pub height: u32,
+    pub alive_cells: Vec<(u32, u32)>,
+    pub dying_cells: Vec<(u32, u32, u32)>,
+    pub rule: Option<String>,
+}
+
+impl Pattern {
+    pub fn new(width: u32, height: u32, alive_cells: Vec<(u32, u32)>) -> Pattern {
+        Pattern {
+            width,
+            height,
+            alive_cells,
+            dying_cells: vec![],
+            rule: None,
+        }
+    }
+
+    pub fn from_grid(grid: &Grid, rule: Option<String>) -> Pattern {
+        let cells = grid.cells.iter().flatten();
+
+        Pattern {
+            width: grid.cells.first().map_or(0, |row| row.len()) as u32,
+            height: grid.cells.len() as u32,
+            alive_cells: cells
+                .clone()
+                .filter(|cell| cell.alive)
+                .map(|cell| (cell.x, cell.y))
+                .collect(),
+            dying_cells: cells
+                .filter(|cell| cell.dying)
+                .map(|cell| (cell.x, cell.y, cell.decay))
+                .collect(),
+            rule,
+        }
+    }
+
+    // Alive cells at any coordinates, moved to start at the top left of their bounding box
+    // and sorted by row.
+    pub fn from_cells(cells: Vec<(i64, i64)>, rule: Option<String>) -> Pattern {
+        let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let max_x = cells.iter().map(|&(x, _)| x).max().unwrap_or(-1);
+        let max_y = cells.iter().map(|&(_, y)| y).max().unwrap_or(-1);
+
+        let mut alive_cells: Vec<(u32, u32)> = cells
+            .iter()
+            .map(|&(x, y)| ((x - min_x) as u32, (y - min_y) as u32))
+            .collect();
+
+        alive_cells.sort_by_key(|&(x, y)| (y, x));
+        alive_cells.dedup();
+
+        Pattern {
+            width: (max_x - min_x + 1) as u32,
+            height: (max_y - min_y + 1) as u32,
+            alive_cells,
+            dying_cells: vec![],
+            rule,
+        }
+    }
+
+    // The same pattern in the middle of a world of at least width by height cells.
+    pub fn centred(&self, width: u32, height: u32) -> Pattern {
+        let width = cmp::max(width, self.width);
+        let height = cmp::max(height, self.height);
+
+        let dx = (width - self.width) / 2;
+        let dy = (height - self.height) / 2;
+
+        Pattern {
+            width,
+            height,
+            alive_cells: self
+                .alive_cells
+                .iter()
+                .map(|&(x, y)| (x + dx, y + dy))
+                .collect(),
+            dying_cells: self
+                .dying_cells
+                .iter()
+                .map(|&(x, y, decay)| (x + dx, y + dy, decay))
+                .collect(),
+            rule: self.rule.clone(),
+        }
+    }
+
+    pub fn to_grid(&self, dead_char: &str, dying_char: &str, alive_char: &str) -> Grid {
+        let mut grid = Grid::new_alive_grid(
+            self.width,
+            self.height,
+            dead_char.to_owned(),
+            dying_char.to_owned(),
+            alive_char.to_owned(),
+            self.alive_cells.clone(),
+            vec![],
+        );
+
+        for &(x, y, decay) in self.dying_cells.iter() {
+            let cell = &grid.cells[y as usize][x as usize];
+            grid.cells[y as usize][x as usize] = cell.set_decaying(decay);
+        }
+
+        grid
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Rle,
+    Plaintext,
+    Life105,
+    Life106,
+    Macrocell,
+}
+
+impl Format {
+    // .lif and .life files may be either Life 1.05 or 1.06 when read, which is told by their
+    // header. .lif files are written as Life 1.06 and .life files as Life 1.05.
+    // .txt files may hold any format, so they are told by their contents instead.
+    pub fn from_extension(path: &str) -> Option<Format> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" => Some(Format::Plaintext),
+            "lif" => Some(Format::Life106),
+            "life" => Some(Format::Life105),
+            "mc" => Some(Format::Macrocell),
+            _ => None,
+        }
+    }
+
+    // The format a file is saved in, given by its extension and defaulting to RLE.
+    pub fn for_writing(path: &str) -> Format {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("txt") => Format::Plaintext,
+            _ => Format::from_extension(path).unwrap_or(Format::Rle),
+        }
+    }
+
+    pub fn from_contents(contents: &str) -> Option<Format> {
+        let mut lines = contents.lines().map(|line| line.trim());
+
+        match lines.next() {
+            Some("#Life 1.05") => return Some(Format::Life105),
+            Some("#Life 1.06") => return Some(Format::Life106),
+            Some(line) if line.starts_with("[M2]") => return Some(Format::Macrocell),
+            _ => (),
+        }
+
+        let first_line = contents
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))?;
+
+        if first_line.starts_with('x') && first_line.contains('=') {
+            Some(Format::Rle)
+        } else if first_line.chars().all(|c| c == '.' || c == 'O' || c == '*') {
+            Some(Format::Plaintext)
+        } else {
+            None
+        }
+    }
+}
+
+// Reads a pattern file, in the format given by its extension or else by its contents.
+pub fn read(path: &str) -> Result<Pattern, String> {
+    let (contents, format) = read_contents(path)?;
+
+    match format {
+        Format::Rle => rle::parse(&contents),
+        Format::Plaintext => plaintext::parse(&contents),
+        Format::Life105 | Format::Life106 => lif::parse(&contents),
+        Format::Macrocell => macrocell::parse(&contents),
+    }
+}
+
+// Reads a macrocell file as its quadtree, without expanding it into cells,
+// or gives None for a pattern file in another format.
+pub fn read_macrocell(path: &str) -> Result<Option<Macrocell>, String> {
+    match read_contents(path)? {
+        (contents, Format::Macrocell) => macrocell::parse_tree(&contents).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn read_contents(path: &str) -> Result<(String, Format), String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read pattern file {}: {}", path, e))?;
+
+    let format = Format::from_extension(path)
+        .or_else(|| Format::from_contents(&contents))
+        .ok_or_else(|| format!("Couldn't tell the format of pattern file {}", path))?;
+
+    Ok((contents, format))
+}
+
+// Saves a pattern seen at a generation, e.g. to snapshot_100.rle for snapshot.rle,
+// in the format given by the file extension, defaulting to RLE.
+// .txt files are written as plaintext.
+pub fn save_snapshot(
+    pattern: &Pattern,
+    snapshot_file: &str,
+    generation: u64,
+) -> Result<PathBuf, String> {
+    let path = Path::new(snapshot_file);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("snapshot");
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("rle");
+    let format = Format::for_writing(snapshot_file);
+
+    let path = path.with_file_name(format!("{}_{}.{}", stem, generation, extension));
+
+    fs::write(&path, write(pattern, format)?)
+        .map_err(|e| format!("Couldn't save snapshot to {}: {}", path.display(), e))?;
+
+    Ok(path)
+}
+
+pub fn write(pattern: &Pattern, format: Format) -> Result<String, String> {
+    match format {
+        Format::Rle => Ok(rle::write(pattern)),
+        Format::Plaintext => Ok(plaintext::write(pattern)),
+        Format::Life105 => lif::write_105(pattern),
+        Format::Life106 => Ok(lif::write_106(pattern)),
+        Format::Macrocell => Ok(macrocell::write(pattern)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_format_from_extension() {
+        assert_eq!(Format::from_extension("glider.rle"), Some(Format::Rle));
+        assert_eq!(
+            Format::from_extension("a/b/Glider.CELLS"),
+            Some(Format::Plaintext)
+        );
+        assert_eq!(Format::from_extension("glider.lif"), Some(Format::Life106));
+        assert_eq!(Format::from_extension("glider.life"), Some(Format::Life105));
+        assert_eq!(Format::from_extension("glider.mc"), Some(Format::Macrocell));
+        assert_eq!(Format::from_extension("glider"), None);
+        assert_eq!(Format::from_extension("glider.txt"), None);
+    }
+
+    #[test]
+    fn detect_format_for_writing() {
+        assert_eq!(Format::for_writing("glider.life"), Format::Life105);
+        assert_eq!(Format::for_writing("glider.TXT"), Format::Plaintext);
+        assert_eq!(Format::for_writing("glider"), Format::Rle);
+    }
+
+    #[test]
+    fn detect_format_from_contents() {
+        assert_eq!(
+            Format::from_contents("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"),
+            Some(Format::Rle)
+        );
+        assert_eq!(
+            Format::from_contents("!Name: Glider\n.O\n..O\nOOO"),
+            Some(Format::Plaintext)
+        );
+        assert_eq!(
+            Format::from_contents("#Life 1.05\n#N"),
+            Some(Format::Life105)
+        );
+        assert_eq!(
+            Format::from_contents("#Life 1.06\n0 0"),
+            Some(Format::Life106)
+        );
+        assert_eq!(
+            Format::from_contents("[M2] (golly 4.2)\n$"),
+            Some(Format::Macrocell)
+        );
+        assert_eq!(Format::from_contents("hello"), None);
+    }
+
+    #[test]
+    fn centred_pattern_keeps_its_cells_and_rule() {
+        let mut pattern = Pattern::new(3, 1, vec![(0, 0), (2, 0)]);
+        pattern.dying_cells = vec![(1, 0, 2)];
+        pattern.rule = Some(String::from("/2/3"));
+
+        let centred = pattern.centred(7, 3);
+
+        assert_eq!(centred.width, 7);
+        assert_eq!(centred.height, 3);
+        assert_eq!(centred.alive_cells, vec![(2, 1), (4, 1)]);
+        assert_eq!(centred.dying_cells, vec![(3, 1, 2)]);
+        assert_eq!(centred.rule, Some(String::from("/2/3")));
+    }
+
+    #[test]
+    fn from_grid_keeps_alive_and_dying_cells() {
+        let grid = Grid::new_alive_grid(
+            3,
+            2,
+            String::new(),
+            String::new(),
+            String::new(),
+            vec![(0, 0), (2, 1)],
+            vec![(1, 1)],
+        );
+
+        let pattern = Pattern::from_grid(&grid, Some(String::from("/2/3")));
+
+        assert_eq!(pattern.width, 3);
+        assert_eq!(pattern.height, 2);
+        assert_eq!(pattern.alive_cells, vec![(0, 0), (2, 1)]);
+        assert_eq!(pattern.dying_cells, vec![(1, 1, 1)]);
+        assert_eq!(pattern.rule, Some(String::from("/2/3")));
+    }
+
+    #[test]
+    fn dying_cells_keep_their_decay_stage_in_grid() {
+        let mut pattern = Pattern::new(2, 1, vec![(0, 0)]);
+        pattern.dying_cells = vec![(1, 0, 2)];
+
+        let grid = pattern.to_grid(".", "x,o", "*");
+
+        assert_eq!(grid.display(), "* o");
+    }
+}
@@ -0,0 +1,87 @@
+use crate::pattern::Pattern;
+
+// Reads a pattern in LifeWiki's plaintext (.cells) format:
+// '!' comment lines, then one line per row with '.' for dead and 'O' for alive cells.
+pub fn parse(contents: &str) -> Result<Pattern, String> {
+    let mut alive_cells: Vec<(u32, u32)> = vec![];
+    let mut width = 0;
+    let mut height = 0;
+
+    for line in contents.lines().filter(|line| !line.starts_with('!')) {
+        let row = line.trim_end();
+
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                'O' | 'o' | '*' => alive_cells.push((x as u32, height)),
+                '.' => (),
+                c => return Err(format!("Unexpected '{}' in plaintext line: {}", c, line)),
+            }
+        }
+
+        width = width.max(row.chars().count() as u32);
+        height += 1;
+    }
+
+    Ok(Pattern::new(width, height, alive_cells))
+}
+
+// Plaintext only has two states, so dying cells are written as dead.
+pub fn write(pattern: &Pattern) -> String {
+    let mut rows = vec![vec!['.'; pattern.width as usize]; pattern.height as usize];
+
+    for &(x, y) in pattern.alive_cells.iter() {
+        rows[y as usize][x as usize] = 'O';
+    }
+
+    let mut lines: Vec<String> = vec![];
+
+    if let Some(ref rule) = pattern.rule {
+        lines.push(format!("!Rule: {}", rule));
+    }
+
+    lines.extend(rows.iter().map(|row| {
+        let row: String = row.iter().collect();
+        row.trim_end_matches('.').to_owned()
+    }));
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_glider() {
+        let pattern = parse(
+            "!Name: Glider
+!
+.O
+..O
+OOO",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.width, 3);
+        assert_eq!(pattern.height, 3);
+        assert_eq!(
+            pattern.alive_cells,
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn write_then_parse_gives_same_cells() {
+        let pattern = Pattern::new(4, 3, vec![(3, 0), (0, 2), (1, 2)]);
+
+        let plaintext = write(&pattern);
+
+        assert_eq!(plaintext, "...O\n\nOO\n");
+        assert_eq!(parse(&plaintext).unwrap(), pattern);
+    }
+
+    #[test]
+    fn reject_unexpected_characters() {
+        assert!(parse(".O\nxO").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::cycle::{CycleDetector, OnStable, Stabilised};
+use crate::pattern;
+use crate::stats::StatsWriter;
+use crate::world::World;
+
+// How far a world has run, from generation 0, with whether it has repeated and the snapshots
+// and statistics asked for. The terminal UI and plain runs both step worlds through it,
+// so they number generations alike.
+pub struct Progress {
+    pub generation: u64,
+    pub detector: CycleDetector,
+    pub stats: Option<StatsWriter>,
+    pub snapshot_at: Vec<u64>,
+    pub snapshot_file: String,
+}
+
+impl Progress {
+    pub fn new(
+        snapshot_at: Vec<u64>,
+        snapshot_file: String,
+        stats: Option<StatsWriter>,
+    ) -> Progress {
+        Progress {
+            generation: 0,
+            detector: CycleDetector::new(),
+            stats,
+            snapshot_at,
+            snapshot_file,
+        }
+    }
+
+    // Worlds settling into objects which each repeat on their own are only found
+    // when something is done once the world stabilises, as finding them is slower.
+    pub fn with_on_stable(self, on_stable: OnStable) -> Progress {
+        Progress {
+            detector: self.detector.with_objects(on_stable != OnStable::Continue),
+            ..self
+        }
+    }
+
+    // Steps the world once, which for some engines is several generations.
+    pub fn step(&mut self, world: &mut World, threads: usize) {
+        world.game.next_parallel(threads);
+        self.generation += world.game.generations_per_step();
+    }
+
+    // Starts again from generation 0, for a new world.
+    pub fn restart(&mut self) {
+        self.generation = 0;
+        self.detector.reset();
+
+        if let Some(ref mut stats) = self.stats {
+            stats.reset();
+        }
+    }
+
+    // Returns when the world first repeats, and None before and after.
+    pub fn observe(&mut self, world: &World) -> Option<Stabilised> {
+        self.detector.observe(self.generation, world.game.as_ref())
+    }
+
+    // Saves the world when a snapshot was asked for at this generation.
+    pub fn save_snapshot(&self, world: &World) -> Result<(), String> {
+        if self.snapshot_at.contains(&self.generation) {
+            pattern::save_snapshot(&world.game.pattern(), &self.snapshot_file, self.generation)?;
+        }
+
+        Ok(())
+    }
+
+    // Closes the statistics file, which must be done before exiting.
+    pub fn finish(&mut self) -> Result<(), String> {
+        match self.stats {
+            Some(ref mut stats) => stats.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_stats(&mut self, world: &World) -> Result<(), String> {
+        match self.stats {
+            Some(ref mut stats) => stats.record(self.generation, world.game.as_ref()),
+            None => Ok(()),
+        }
+    }
+}
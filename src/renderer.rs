@@ -0,0 +1,314 @@
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{Clear, ClearType};
+use std::io::{self, Write};
+
+// Synchronized updates stop the terminal showing a frame until it has all been written.
+// Terminals which don't support them ignore these codes.
+const BEGIN_SYNCHRONIZED_UPDATE: &str = "\x1B[?2026h";
+const END_SYNCHRONIZED_UPDATE: &str = "\x1B[?2026l";
+
+// Unchanged characters between two changes are written again when there are fewer
+// of them than this, as that's shorter than moving the cursor past them.
+const MAX_GAP: usize = 4;
+
+// A character and the colours it's drawn in; None leaves the terminal's own colour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    pub c: char,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Glyph {
+    pub fn new(c: char, foreground: Option<Color>) -> Glyph {
+        Glyph {
+            c,
+            foreground,
+            background: None,
+        }
+    }
+
+    pub fn plain(c: char) -> Glyph {
+        Glyph::new(c, None)
+    }
+
+    fn colours(&self) -> (Option<Color>, Option<Color>) {
+        (self.foreground, self.background)
+    }
+}
+
+pub fn plain(line: &str) -> Vec<Glyph> {
+    line.chars().map(Glyph::plain).collect()
+}
+
+// Draws frames of text to a terminal, keeping the last frame drawn so that
+// only the characters which changed are written again.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Renderer {
+    previous: Option<Vec<Vec<Glyph>>>,
+    previous_highlight: Option<(usize, usize)>,
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    // Forgets the last frame, so the next one is drawn in full, e.g. after the terminal is resized.
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+
+    // Draws the lines of a frame from the top left of the terminal, leaving the cursor
+    // below them. Each character is taken to fill one column.
+    // The character at the highlighted (column, row) is drawn in reverse video.
+    pub fn render<W: Write>(
+        &mut self,
+        out: &mut W,
+        lines: &[Vec<Glyph>],
+        highlight: Option<(usize, usize)>,
+    ) -> io::Result<()> {
+        let frame = lines.to_vec();
+
+        queue!(out, Print(BEGIN_SYNCHRONIZED_UPDATE))?;
+
+        let previous = match self.previous.take() {
+            Some(previous) => previous,
+            None => {
+                queue!(out, Clear(ClearType::All))?;
+                vec![]
+            }
+        };
+
+        let highlight_moved = highlight != self.previous_highlight;
+        let no_characters = vec![];
+
+        for (y, line) in frame.iter().enumerate() {
+            let previous_line = previous.get(y).unwrap_or(&no_characters);
+
+            // A highlighted character that moves is redrawn where it was, to remove the highlight.
+            let changed = |x: usize| {
+                previous_line.get(x) != Some(&line[x])
+                    || (highlight_moved && self.previous_highlight == Some((x, y)))
+            };
+
+            let mut x = 0;
+
+            while x < line.len() {
+                if !changed(x) {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                let mut end = x;
+
+                while x < line.len() && x - end < MAX_GAP {
+                    if changed(x) {
+                        end = x + 1;
+                    }
+
+                    x += 1;
+                }
+
+                queue!(out, MoveTo(start as u16, y as u16))?;
+                print_run(out, &line[start..end])?;
+            }
+
+            if previous_line.len() > line.len() {
+                queue!(
+                    out,
+                    MoveTo(line.len() as u16, y as u16),
+                    Clear(ClearType::UntilNewLine)
+                )?;
+            }
+        }
+
+        if previous.len() > frame.len() {
+            queue!(
+                out,
+                MoveTo(0, frame.len() as u16),
+                Clear(ClearType::FromCursorDown)
+            )?;
+        }
+
+        if let Some((x, y)) = highlight {
+            if let Some(glyph) = frame.get(y).and_then(|line| line.get(x)) {
+                queue!(
+                    out,
+                    MoveTo(x as u16, y as u16),
+                    SetAttribute(Attribute::Reverse)
+                )?;
+                print_run(out, &[*glyph])?;
+                queue!(out, SetAttribute(Attribute::NoReverse))?;
+            }
+        }
+
+        queue!(
+            out,
+            MoveTo(0, frame.len() as u16),
+            Print(END_SYNCHRONIZED_UPDATE)
+        )?;
+
+        self.previous = Some(frame);
+        self.previous_highlight = highlight;
+
+        out.flush()
+    }
+}
+
+// Writes glyphs at the cursor, changing colour only where it differs from the glyph
+// before, and going back to the terminal's own colours afterwards.
+fn print_run<W: Write>(out: &mut W, glyphs: &[Glyph]) -> io::Result<()> {
+    let mut current = (None, None);
+    let mut text = String::new();
+
+    for glyph in glyphs {
+        if glyph.colours() != current {
+            if !text.is_empty() {
+                queue!(out, Print(&text))?;
+                text.clear();
+            }
+
+            if current != (None, None) {
+                queue!(out, ResetColor)?;
+            }
+
+            if let Some(colour) = glyph.foreground {
+                queue!(out, SetForegroundColor(colour))?;
+            }
+
+            if let Some(colour) = glyph.background {
+                queue!(out, SetBackgroundColor(colour))?;
+            }
+
+            current = glyph.colours();
+        }
+
+        text.push(glyph.c);
+    }
+
+    if !text.is_empty() {
+        queue!(out, Print(&text))?;
+    }
+
+    if current != (None, None) {
+        queue!(out, ResetColor)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(frame: &[&str]) -> Vec<Vec<Glyph>> {
+        frame.iter().map(|line| plain(line)).collect()
+    }
+
+    fn render(
+        renderer: &mut Renderer,
+        frame: &[&str],
+        highlight: Option<(usize, usize)>,
+    ) -> String {
+        let mut out: Vec<u8> = vec![];
+        renderer.render(&mut out, &lines(frame), highlight).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn first_frame_clears_the_screen_and_draws_everything() {
+        let mut renderer = Renderer::new();
+
+        assert_eq!(
+            render(&mut renderer, &[". #", "# ."], None),
+            "\x1B[?2026h\x1B[2J\x1B[1;1H. #\x1B[2;1H# .\x1B[3;1H\x1B[?2026l"
+        );
+    }
+
+    #[test]
+    fn only_changed_characters_are_drawn() {
+        let mut renderer = Renderer::new();
+        render(&mut renderer, &[". . . .", ". . . ."], None);
+
+        assert_eq!(
+            render(&mut renderer, &[". # # .", ". . . ."], None),
+            "\x1B[?2026h\x1B[1;3H# #\x1B[3;1H\x1B[?2026l"
+        );
+
+        assert_eq!(
+            render(&mut renderer, &[". # # .", ". . . ."], None),
+            "\x1B[?2026h\x1B[3;1H\x1B[?2026l"
+        );
+
+        assert_eq!(
+            render(&mut renderer, &["# # # .", ". . . #"], None),
+            "\x1B[?2026h\x1B[1;1H#\x1B[2;7H#\x1B[3;1H\x1B[?2026l"
+        );
+    }
+
+    #[test]
+    fn shorter_frames_clear_what_was_left() {
+        let mut renderer = Renderer::new();
+        render(&mut renderer, &["population = 100", "#", "#"], None);
+
+        assert_eq!(
+            render(&mut renderer, &["population = 9"], None),
+            "\x1B[?2026h\x1B[1;14H9\x1B[1;15H\x1B[K\x1B[2;1H\x1B[J\x1B[2;1H\x1B[?2026l"
+        );
+    }
+
+    #[test]
+    fn moving_the_highlight_redraws_where_it_was() {
+        let mut renderer = Renderer::new();
+        render(&mut renderer, &[". ."], Some((0, 0)));
+
+        assert_eq!(
+            render(&mut renderer, &[". ."], Some((2, 0))),
+            "\x1B[?2026h\x1B[1;1H.\x1B[1;3H\x1B[7m.\x1B[27m\x1B[2;1H\x1B[?2026l"
+        );
+    }
+
+    #[test]
+    fn invalidate_draws_the_next_frame_in_full() {
+        let mut renderer = Renderer::new();
+        render(&mut renderer, &["#"], None);
+
+        renderer.invalidate();
+
+        assert_eq!(
+            render(&mut renderer, &["#"], None),
+            "\x1B[?2026h\x1B[2J\x1B[1;1H#\x1B[2;1H\x1B[?2026l"
+        );
+    }
+
+    #[test]
+    fn colours_change_only_between_differently_coloured_glyphs() {
+        let mut renderer = Renderer::new();
+        let mut out: Vec<u8> = vec![];
+
+        let red = Glyph::new('#', Some(Color::Red));
+        let line = vec![
+            red,
+            red,
+            Glyph::plain('.'),
+            Glyph {
+                background: Some(Color::Blue),
+                ..red
+            },
+        ];
+
+        renderer.render(&mut out, &[line], None).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1B[?2026h\x1B[2J\x1B[1;1H\x1B[38;5;9m##\x1B[0m.\x1B[38;5;9m\x1B[48;5;12m#\x1B[0m\x1B[2;1H\x1B[?2026l"
+        );
+    }
+}
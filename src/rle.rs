@@ -0,0 +1,384 @@
+use std::cmp;
+
+use crate::pattern::Pattern;
+
+// Reads a pattern in Run Length Encoded format, as used by Golly and LifeWiki:
+// '#' comment lines, a header such as "x = 3, y = 3, rule = B3/S23",
+// then runs of cells ending with '!'. Two-state cells are 'b' (dead) and 'o' (alive);
+// multi-state cells are '.' (dead) and 'A' to 'X', optionally prefixed with 'p' to 'y',
+// for states 1 to 255. For Generations rules, state 1 is alive and the rest are decay stages.
+pub fn parse(contents: &str) -> Result<Pattern, String> {
+    let mut width = 0;
+    let mut height = 0;
+    let mut rule = None;
+    let mut header_found = false;
+
+    let mut alive_cells: Vec<(u32, u32)> = vec![];
+    let mut dying_cells: Vec<(u32, u32, u32)> = vec![];
+
+    let mut x: u32 = 0;
+    let mut y: u32 = 0;
+    let mut run: Option<u32> = None;
+    let mut prefix: Option<char> = None;
+
+    'lines: for line in contents.lines() {
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if !header_found {
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(old_rule) = comment.strip_prefix('r') {
+                    rule = Some(old_rule.trim().to_owned());
+                }
+                continue;
+            }
+
+            for (key, value) in header_values(line)? {
+                match key.as_str() {
+                    "x" => width = number(&value, line)?,
+                    "y" => height = number(&value, line)?,
+                    "rule" => rule = Some(value),
+                    _ => (),
+                }
+            }
+
+            header_found = true;
+            continue;
+        }
+
+        // Runs past the largest coordinate are an error rather than wrapping around.
+        let past = |n: u32, count: u32| {
+            n.checked_add(count)
+                .ok_or_else(|| format!("Pattern too large in RLE line: {}", line))
+        };
+
+        for c in line.chars() {
+            let count = run.unwrap_or(1);
+
+            match c {
+                '0'..='9' if prefix.is_none() => {
+                    let digit = c.to_digit(10).unwrap();
+                    run = Some(
+                        run.unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|n| n.checked_add(digit))
+                            .ok_or_else(|| format!("Run count too large in RLE line: {}", line))?,
+                    );
+                    continue;
+                }
+                'b' | '.' if prefix.is_none() => x = past(x, count)?,
+                'o' if prefix.is_none() => {
+                    let end = past(x, count)?;
+                    alive_cells.extend((x..end).map(|cx| (cx, y)));
+                    x = end;
+                }
+                'p'..='y' if prefix.is_none() => {
+                    prefix = Some(c);
+                    continue;
+                }
+                'A'..='X' => {
+                    let state = state(prefix, c)
+                        .ok_or_else(|| format!("Invalid cell state in RLE line: {}", line))?;
+
+                    let end = past(x, count)?;
+
+                    if state == 1 {
+                        alive_cells.extend((x..end).map(|cx| (cx, y)));
+                    } else {
+                        dying_cells.extend((x..end).map(|cx| (cx, y, state - 1)));
+                    }
+
+                    x = end;
+                    prefix = None;
+                }
+                '$' if prefix.is_none() => {
+                    y = past(y, count)?;
+                    x = 0;
+                }
+                '!' if prefix.is_none() => break 'lines,
+                c if c.is_whitespace() && run.is_none() && prefix.is_none() => (),
+                c => return Err(format!("Unexpected '{}' in RLE line: {}", c, line)),
+            }
+
+            run = None;
+            width = cmp::max(width, x);
+            height = cmp::max(height, if x > 0 { past(y, 1)? } else { y });
+        }
+    }
+
+    if !header_found {
+        return Err("Missing RLE header line, e.g. x = 3, y = 3".to_owned());
+    }
+
+    Ok(Pattern {
+        width,
+        height,
+        alive_cells,
+        dying_cells,
+        rule,
+    })
+}
+
+// Writes a pattern in Run Length Encoded format, with lines of at most 70 characters.
+// Patterns with dying cells are written with multi-state cells.
+pub fn write(pattern: &Pattern) -> String {
+    let multi_state = !pattern.dying_cells.is_empty();
+
+    let mut states = vec![vec![0; pattern.width as usize]; pattern.height as usize];
+
+    for &(x, y) in pattern.alive_cells.iter() {
+        states[y as usize][x as usize] = 1;
+    }
+
+    for &(x, y, decay) in pattern.dying_cells.iter() {
+        states[y as usize][x as usize] = decay + 1;
+    }
+
+    let mut runs: Vec<(u32, String)> = vec![];
+    let mut blank_rows = 0;
+
+    for row in states.iter() {
+        let last_alive = match row.iter().rposition(|&state| state != 0) {
+            Some(last) => last,
+            None => {
+                blank_rows += 1;
+                continue;
+            }
+        };
+
+        // Rows end with '$', and the blank rows before this one add to the run.
+        let line_ends = if runs.is_empty() {
+            blank_rows
+        } else {
+            blank_rows + 1
+        };
+
+        if line_ends > 0 {
+            push_run(&mut runs, line_ends, "$".to_owned());
+        }
+
+        blank_rows = 0;
+
+        for &state in row[..=last_alive].iter() {
+            push_run(&mut runs, 1, state_tag(state, multi_state));
+        }
+    }
+
+    let mut lines = vec![match pattern.rule {
+        Some(ref rule) => format!(
+            "x = {}, y = {}, rule = {}",
+            pattern.width, pattern.height, rule
+        ),
+        None => format!("x = {}, y = {}", pattern.width, pattern.height),
+    }];
+
+    let mut line = String::new();
+
+    for (count, tag) in runs
+        .iter()
+        .map(|(count, tag)| (*count, tag.clone()))
+        .chain(vec![(1, "!".to_owned())])
+    {
+        let run = if count > 1 {
+            format!("{}{}", count, tag)
+        } else {
+            tag
+        };
+
+        if line.len() + run.len() > 70 {
+            lines.push(line);
+            line = String::new();
+        }
+
+        line.push_str(&run);
+    }
+
+    lines.push(line);
+
+    lines.join("\n") + "\n"
+}
+
+fn push_run(runs: &mut Vec<(u32, String)>, count: u32, tag: String) {
+    match runs.last_mut() {
+        Some((last_count, last_tag)) if *last_tag == tag => *last_count += count,
+        _ => runs.push((count, tag)),
+    }
+}
+
+fn state_tag(state: u32, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => "b".to_owned(),
+        (_, false) => "o".to_owned(),
+        (0, true) => ".".to_owned(),
+        (state, true) if state <= 24 => ((b'A' + (state - 1) as u8) as char).to_string(),
+        (state, true) => format!(
+            "{}{}",
+            (b'p' + ((state - 25) / 24) as u8) as char,
+            (b'A' + ((state - 25) % 24) as u8) as char
+        ),
+    }
+}
+
+fn header_values(line: &str) -> Result<Vec<(String, String)>, String> {
+    line.split(',')
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => {
+                    Ok((key.trim().to_lowercase(), value.trim().to_owned()))
+                }
+                _ => Err(format!("Invalid RLE header line: {}", line)),
+            }
+        })
+        .collect()
+}
+
+fn number(value: &str, line: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid RLE header line: {}", line))
+}
+
+// 'A' to 'X' are states 1 to 24, 'pA' to 'pX' are 25 to 48, and so on up to 'yO', 255.
+fn state(prefix: Option<char>, c: char) -> Option<u32> {
+    let offset = match prefix {
+        Some(p) => (p as u32 - 'p' as u32 + 1) * 24,
+        None => 0,
+    };
+
+    let state = offset + (c as u32 - 'A' as u32) + 1;
+
+    if state <= 255 {
+        Some(state)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_glider() {
+        let pattern = parse(
+            "#N Glider
+#C A comment
+x = 3, y = 3, rule = B3/S23
+bob$2bo$3o!",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.width, 3);
+        assert_eq!(pattern.height, 3);
+        assert_eq!(pattern.rule, Some(String::from("B3/S23")));
+        assert_eq!(
+            pattern.alive_cells,
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn parse_runs_across_lines_and_blank_rows() {
+        let pattern = parse(
+            "x = 12, y = 4
+2o8b
+2o2$
+10b2o!",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.width, 12);
+        assert_eq!(pattern.height, 4);
+        assert_eq!(
+            pattern.alive_cells,
+            vec![(0, 0), (1, 0), (10, 0), (11, 0), (10, 2), (11, 2)]
+        );
+    }
+
+    #[test]
+    fn parse_multi_state_cells() {
+        let pattern = parse(
+            "x = 4, y = 1, rule = /2/3
+A.BpA!",
+        )
+        .unwrap();
+
+        assert_eq!(pattern.alive_cells, vec![(0, 0)]);
+        assert_eq!(pattern.dying_cells, vec![(2, 0, 1), (3, 0, 24)]);
+        assert_eq!(pattern.rule, Some(String::from("/2/3")));
+    }
+
+    #[test]
+    fn header_size_grows_to_fit_cells() {
+        let pattern = parse("x = 1, y = 1\n3o$o!").unwrap();
+
+        assert_eq!(pattern.width, 3);
+        assert_eq!(pattern.height, 2);
+    }
+
+    #[test]
+    fn write_glider() {
+        let mut pattern = Pattern::new(5, 4, vec![(1, 1), (2, 2), (0, 3), (1, 3), (2, 3)]);
+        pattern.rule = Some(String::from("B3/S23"));
+
+        assert_eq!(
+            write(&pattern),
+            "x = 5, y = 4, rule = B3/S23
+$bo$2bo$3o!
+"
+        );
+    }
+
+    #[test]
+    fn write_multi_state_cells() {
+        let mut pattern = Pattern::new(5, 3, vec![(0, 0), (1, 0)]);
+        pattern.dying_cells = vec![(3, 0, 1), (4, 2, 24)];
+        pattern.rule = Some(String::from("/2/3"));
+
+        assert_eq!(
+            write(&pattern),
+            "x = 5, y = 3, rule = /2/3
+2A.B2$4.pA!
+"
+        );
+    }
+
+    #[test]
+    fn write_wraps_long_lines() {
+        let pattern = Pattern::new(100, 1, (0..100).step_by(2).map(|x| (x, 0)).collect());
+
+        let rle = write(&pattern);
+
+        assert!(rle.lines().all(|line| line.len() <= 70));
+        assert_eq!(parse(&rle).unwrap(), pattern);
+    }
+
+    #[test]
+    fn write_then_parse_gives_same_pattern() {
+        let mut pattern = Pattern::new(6, 6, vec![(0, 2), (3, 2), (5, 5)]);
+        pattern.dying_cells = vec![(2, 4, 1)];
+        pattern.rule = Some(String::from("/2/3"));
+
+        assert_eq!(parse(&write(&pattern)).unwrap(), pattern);
+    }
+
+    #[test]
+    fn reject_invalid_rle() {
+        assert!(parse("bo$ob!").is_err());
+        assert!(parse("x = 3, y = 3\nbqo!").is_err());
+        assert!(parse("x = three, y = 3\nbo!").is_err());
+    }
+
+    #[test]
+    fn reject_runs_past_the_largest_coordinate() {
+        assert!(parse("x = 1, y = 1\n4294967295b2o!").is_err());
+        assert!(parse("x = 1, y = 1\n2b4294967295o!").is_err());
+        assert!(parse("x = 1, y = 1\n4294967295bpA!").is_err());
+        assert!(parse("x = 1, y = 1\n4294967295$o!").is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+
+// A Life-like rule, described by the neighbour counts on which a dead cell
+// is born and the neighbour counts on which an alive cell survives.
+// Conway's Game of Life is B3/S23.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub birth: Vec<usize>,
+    pub survival: Vec<usize>,
+}
+
+impl Rule {
+    pub fn conway() -> Rule {
+        Rule {
+            birth: vec![3],
+            survival: vec![2, 3],
+        }
+    }
+
+    // Accepts both B/S notation ("B36/S23", "S23/B36") and
+    // the older S/B notation ("23/36").
+    pub fn parse(rulestring: &str) -> Result<Rule, String> {
+        let parts: Vec<&str> = rulestring.trim().split('/').collect();
+
+        if parts.len() != 2 {
+            return Err(format!("Invalid rulestring: {}", rulestring));
+        }
+
+        let (birth, survival) = match (prefix(parts[0]), prefix(parts[1])) {
+            (Some('B'), Some('S')) => (&parts[0][1..], &parts[1][1..]),
+            (Some('S'), Some('B')) => (&parts[1][1..], &parts[0][1..]),
+            (None, None) => (parts[1], parts[0]),
+            _ => return Err(format!("Invalid rulestring: {}", rulestring)),
+        };
+
+        Ok(Rule {
+            birth: neighbour_counts(birth, rulestring)?,
+            survival: neighbour_counts(survival, rulestring)?,
+        })
+    }
+
+    pub fn is_alive(&self, alive: bool, alive_neighbours_count: usize) -> bool {
+        if alive {
+            self.survival.contains(&alive_neighbours_count)
+        } else {
+            self.birth.contains(&alive_neighbours_count)
+        }
+    }
+
+    // The older S/B notation, e.g. "23/3".
+    pub fn survival_birth_notation(&self) -> String {
+        format!("{}/{}", digits(&self.survival), digits(&self.birth))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+// A Generations rule: as Life-like, but alive cells which don't survive
+// decay through `states - 2` dying stages before they are dead.
+// Brian's Brain is /2/3 (or B2/S/C3).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationsRule {
+    pub birth: Vec<usize>,
+    pub survival: Vec<usize>,
+    pub states: u32,
+}
+
+impl GenerationsRule {
+    pub fn brians_brain() -> GenerationsRule {
+        GenerationsRule {
+            birth: vec![2],
+            survival: vec![],
+            states: 3,
+        }
+    }
+
+    // Accepts B/S/C notation ("B2/S/C3", in any order) and
+    // the older S/B/C notation ("/2/3", "345/2/4").
+    pub fn parse(rulestring: &str) -> Result<GenerationsRule, String> {
+        let parts: Vec<&str> = rulestring.trim().split('/').collect();
+
+        if parts.len() != 3 {
+            return Err(format!("Invalid rulestring: {}", rulestring));
+        }
+
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+
+        if parts.iter().all(|part| prefix(part).is_none()) {
+            survival = Some(parts[0]);
+            birth = Some(parts[1]);
+            states = Some(parts[2]);
+        } else {
+            for part in parts {
+                match prefix(part) {
+                    Some('B') => birth = Some(&part[1..]),
+                    Some('S') => survival = Some(&part[1..]),
+                    Some('C') | Some('G') => states = Some(&part[1..]),
+                    _ => return Err(format!("Invalid rulestring: {}", rulestring)),
+                }
+            }
+        }
+
+        match (birth, survival, states.map(|n| n.parse::<u32>())) {
+            (Some(birth), Some(survival), Some(Ok(states))) if (2..=256).contains(&states) => {
+                Ok(GenerationsRule {
+                    birth: neighbour_counts(birth, rulestring)?,
+                    survival: neighbour_counts(survival, rulestring)?,
+                    states,
+                })
+            }
+            _ => Err(format!("Invalid rulestring: {}", rulestring)),
+        }
+    }
+
+    pub fn is_alive(&self, alive: bool, dying: bool, alive_neighbours_count: usize) -> bool {
+        if alive {
+            self.survival.contains(&alive_neighbours_count)
+        } else {
+            !dying && self.birth.contains(&alive_neighbours_count)
+        }
+    }
+
+    // The decay stage a cell moves to when it is not alive in the next generation,
+    // zero meaning dead.
+    pub fn next_decay(&self, alive: bool, decay: u32) -> u32 {
+        let next_decay = if alive {
+            1
+        } else if decay > 0 {
+            decay + 1
+        } else {
+            0
+        };
+
+        if next_decay > self.states - 2 {
+            0
+        } else {
+            next_decay
+        }
+    }
+}
+
+impl fmt::Display for GenerationsRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            digits(&self.survival),
+            digits(&self.birth),
+            self.states
+        )
+    }
+}
+
+fn digits(counts: &[usize]) -> String {
+    counts.iter().map(|n| n.to_string()).collect()
+}
+
+fn prefix(part: &str) -> Option<char> {
+    part.chars()
+        .next()
+        .map(|c| c.to_ascii_uppercase())
+        .filter(|c| c.is_ascii_alphabetic())
+}
+
+fn neighbour_counts(digits: &str, rulestring: &str) -> Result<Vec<usize>, String> {
+    let mut counts: Vec<usize> = vec![];
+
+    for c in digits.chars() {
+        match c.to_digit(10) {
+            Some(n) if n <= 8 => {
+                if !counts.contains(&(n as usize)) {
+                    counts.push(n as usize)
+                }
+            }
+            _ => return Err(format!("Invalid rulestring: {}", rulestring)),
+        }
+    }
+
+    counts.sort_unstable();
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_birth_survival_notation() {
+        let rule = Rule::parse("B36/S23").unwrap();
+
+        assert_eq!(rule.birth, vec![3, 6]);
+        assert_eq!(rule.survival, vec![2, 3]);
+    }
+
+    #[test]
+    fn parse_survival_birth_notation() {
+        let rule = Rule::parse("23/3").unwrap();
+
+        assert_eq!(rule, Rule::conway());
+    }
+
+    #[test]
+    fn parse_lowercase_and_reversed_notation() {
+        let rule = Rule::parse("s23/b3").unwrap();
+
+        assert_eq!(rule, Rule::conway());
+    }
+
+    #[test]
+    fn parse_empty_survival() {
+        let rule = Rule::parse("B2/S").unwrap();
+
+        assert_eq!(rule.birth, vec![2]);
+        assert_eq!(rule.survival, vec![]);
+    }
+
+    #[test]
+    fn reject_invalid_rulestrings() {
+        assert!(Rule::parse("B9/S23").is_err());
+        assert!(Rule::parse("B3S23").is_err());
+        assert!(Rule::parse("game_of_life").is_err());
+        assert!(Rule::parse("B3/23").is_err());
+    }
+
+    #[test]
+    fn display_in_birth_survival_notation() {
+        let rule = Rule::parse("34678/3678").unwrap();
+
+        assert_eq!(rule.to_string(), "B3678/S34678");
+    }
+
+    #[test]
+    fn parse_generations_survival_birth_states_notation() {
+        let rule = GenerationsRule::parse("/2/3").unwrap();
+
+        assert_eq!(rule, GenerationsRule::brians_brain());
+    }
+
+    #[test]
+    fn parse_generations_birth_survival_states_notation() {
+        let rule = GenerationsRule::parse("B2/S345/C4").unwrap();
+
+        assert_eq!(rule, GenerationsRule::parse("345/2/4").unwrap());
+        assert_eq!(rule.states, 4);
+    }
+
+    #[test]
+    fn reject_invalid_generations_rulestrings() {
+        assert!(GenerationsRule::parse("B3/S23").is_err());
+        assert!(GenerationsRule::parse("/2/1").is_err());
+        assert!(GenerationsRule::parse("/2/x").is_err());
+        assert!(GenerationsRule::parse("B2/S/X3").is_err());
+    }
+
+    #[test]
+    fn alive_cell_decays_through_each_dying_stage() {
+        let rule = GenerationsRule::parse("345/2/4").unwrap();
+
+        assert_eq!(rule.next_decay(true, 0), 1);
+        assert_eq!(rule.next_decay(false, 1), 2);
+        assert_eq!(rule.next_decay(false, 2), 0);
+        assert_eq!(rule.next_decay(false, 0), 0);
+    }
+
+    #[test]
+    fn two_state_generations_rule_has_no_dying_stage() {
+        let rule = GenerationsRule::parse("23/3/2").unwrap();
+
+        assert_eq!(rule.next_decay(true, 0), 0);
+    }
+}
@@ -0,0 +1,634 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+
+use crate::cycle::CycleDetector;
+use crate::grid::Grid;
+use crate::objects::{self, Connectivity};
+use crate::parallel::map_rows;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::sparse_life::SparseLife;
+use crate::viewport::Viewport;
+use crate::world::Simulation;
+
+type Cells = HashSet<(i64, i64)>;
+
+// Alive cells with up to this many dead cells between them are first taken as one object.
+const GAP: u32 = 1;
+
+// How many generations an object is run on its own to find its period.
+const PERIOD_LIMIT: u64 = 120;
+
+// A soup has settled once its census is the same this many generations apart,
+// for soups which send spaceships away, so never repeat.
+const CHECK_INTERVAL: u64 = 120;
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Kind {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Unknown,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::StillLife => write!(f, "still life"),
+            Kind::Oscillator => write!(f, "oscillator"),
+            Kind::Spaceship => write!(f, "spaceship"),
+            Kind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// How many of an object were found, and the seeds of the soups they were found in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub kind: Kind,
+    pub count: u64,
+    pub seeds: Vec<u32>,
+}
+
+// The objects left by each soup once settled, by apgcode, e.g. xs4_33 for a block.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Census {
+    pub soups: u64,
+    pub objects: BTreeMap<String, Entry>,
+    pub unsettled: Vec<u32>,
+}
+
+impl Census {
+    fn add(&mut self, seed: u32, objects: Option<Vec<(String, Kind)>>) {
+        self.soups += 1;
+
+        let objects = match objects {
+            Some(o) => o,
+            None => return self.unsettled.push(seed),
+        };
+
+        for (name, kind) in objects {
+            let entry = self.objects.entry(name).or_insert(Entry {
+                kind,
+                count: 0,
+                seeds: vec![],
+            });
+
+            entry.count += 1;
+
+            if entry.seeds.last() != Some(&seed) {
+                entry.seeds.push(seed);
+            }
+        }
+    }
+
+    // Objects found in at most `most_soups` soups, the rarest first.
+    pub fn rare(&self, most_soups: usize) -> Vec<(&String, &Entry)> {
+        let mut rare: Vec<(&String, &Entry)> = self
+            .objects
+            .iter()
+            .filter(|(_, entry)| entry.seeds.len() <= most_soups)
+            .collect();
+
+        rare.sort_by_key(|(name, entry)| (entry.seeds.len(), entry.count, name.to_owned()));
+        rare
+    }
+
+    pub fn report(&self, most_soups: usize) -> String {
+        let describe = |name: &str, entry: &Entry| match common_name(name) {
+            Some(common) => format!("{} ({})", name, common),
+            None => format!("{} ({})", name, entry.kind),
+        };
+
+        let mut by_count: Vec<(&String, &Entry)> = self.objects.iter().collect();
+        by_count.sort_by_key(|(name, entry)| (u64::MAX - entry.count, name.to_owned()));
+
+        let mut lines = vec![
+            format!(
+                "{} soups, {} settled",
+                self.soups,
+                self.soups - self.unsettled.len() as u64
+            ),
+            format!("{:>8}  {:>6}  object", "count", "soups"),
+        ];
+
+        lines.extend(by_count.iter().map(|(name, entry)| {
+            format!(
+                "{:>8}  {:>6}  {}",
+                entry.count,
+                entry.seeds.len(),
+                describe(name, entry)
+            )
+        }));
+
+        let rare = self.rare(most_soups);
+
+        if !rare.is_empty() {
+            lines.push(format!(
+                "Rare objects, found in at most {} soups:",
+                most_soups
+            ));
+
+            lines.extend(rare.iter().map(|(name, entry)| {
+                format!(
+                    "{}: {} in seeds {}",
+                    describe(name, entry),
+                    entry.count,
+                    join(&entry.seeds)
+                )
+            }));
+        }
+
+        if !self.unsettled.is_empty() {
+            lines.push(format!("Didn't settle: seeds {}", join(&self.unsettled)));
+        }
+
+        lines.join("\n")
+    }
+}
+
+// Runs the soup made from each seed until it settles, or for at most `max_generations`,
+// and counts the objects left behind. Soups are run on an unbounded plane, so spaceships
+// fly away rather than crashing into an edge.
+pub fn search<S>(
+    seeds: Range<u32>,
+    rule: &Rule,
+    max_generations: u64,
+    threads: usize,
+    soup: S,
+) -> Census
+where
+    S: Fn(u32) -> Pattern + Sync,
+{
+    let soups = map_rows(seeds.len(), threads, |i| {
+        let seed = seeds.start + i as u32;
+        let pattern = soup(seed);
+
+        let cells = pattern
+            .alive_cells
+            .iter()
+            .map(|&(x, y)| (x as i64, y as i64))
+            .collect();
+
+        (seed, settle(cells, rule, max_generations))
+    });
+
+    let mut census = Census::default();
+
+    for (seed, objects) in soups {
+        census.add(seed, objects);
+    }
+
+    census
+}
+
+// The objects a soup settles into, sorted by name, or None if it doesn't settle in time.
+fn settle(cells: Cells, rule: &Rule, max_generations: u64) -> Option<Vec<(String, Kind)>> {
+    let mut world = sparse(cells, rule);
+    let mut detector = CycleDetector::new();
+    let mut previous = None;
+
+    for generation in 0..=max_generations {
+        if detector.observe(generation, &world).is_some() {
+            return Some(classify_all(&world.alive_cells, rule));
+        }
+
+        if generation > 0 && generation % CHECK_INTERVAL == 0 {
+            let objects = classify_all(&world.alive_cells, rule);
+
+            if previous.as_ref() == Some(&objects) {
+                return Some(objects);
+            }
+
+            previous = Some(objects);
+        }
+
+        world.next();
+    }
+
+    None
+}
+
+fn classify_all(cells: &Cells, rule: &Rule) -> Vec<(String, Kind)> {
+    let mut objects: Vec<(String, Kind)> = separate(cells, Connectivity::MooreGap(GAP))
+        .iter()
+        .flat_map(|cluster| split(cluster, rule))
+        .map(|object| (object.name, object.kind))
+        .collect();
+
+    objects.sort();
+    objects
+}
+
+fn separate(cells: &Cells, connectivity: Connectivity) -> Vec<Cells> {
+    objects::label(cells.iter().copied(), connectivity)
+        .into_iter()
+        .map(|object| object.cells)
+        .collect()
+}
+
+// Objects close together, such as a blinker beside a block, are counted apart when
+// the touching parts of the cluster are each an object and run apart as they do together.
+// Otherwise the cluster is one object, as some, such as the toad, fall apart in some phases.
+fn split(cluster: &Cells, rule: &Rule) -> Vec<Classified> {
+    let whole = classify(cluster, rule);
+    let parts = separate(cluster, Connectivity::Moore);
+
+    if whole.kind == Kind::Unknown || parts.len() == 1 {
+        return vec![whole];
+    }
+
+    let objects: Vec<Classified> = parts.iter().map(|part| classify(part, rule)).collect();
+
+    if objects.iter().any(|object| object.kind == Kind::Unknown)
+        || !independent(cluster, &parts, rule, whole.period * 2)
+    {
+        return vec![whole];
+    }
+
+    objects
+}
+
+// Whether the parts run apart for `generations` give the same cells as run together.
+fn independent(cluster: &Cells, parts: &[Cells], rule: &Rule, generations: u64) -> bool {
+    let mut whole = sparse(cluster.clone(), rule);
+    let mut parts: Vec<SparseLife> = parts
+        .iter()
+        .map(|part| sparse(part.clone(), rule))
+        .collect();
+
+    (0..generations).all(|_| {
+        whole.next();
+        parts.iter_mut().for_each(|part| part.next());
+
+        let together: Cells = parts
+            .iter()
+            .flat_map(|part| part.alive_cells.iter().copied())
+            .collect();
+
+        together == whole.alive_cells
+    })
+}
+
+struct Classified {
+    name: String,
+    kind: Kind,
+    period: u64,
+}
+
+// Runs an object on its own to find whether it's a still life, oscillator or spaceship,
+// and names it by its apgcode: xs, xp or xq with the population or period, then the
+// smallest encoding of any of its phases in any orientation.
+fn classify(object: &Cells, rule: &Rule) -> Classified {
+    let mut world = sparse(object.clone(), rule);
+    let mut detector = CycleDetector::new();
+    let mut phases: Vec<Cells> = vec![];
+
+    for generation in 0..=PERIOD_LIMIT {
+        if let Some(stabilised) = detector.observe(generation, &world) {
+            if stabilised.generation != 0 || stabilised.extinct {
+                break;
+            }
+
+            let period = stabilised.period;
+
+            let (prefix, kind) = match stabilised.displacement {
+                (0, 0) if period > 1 => (format!("xp{}", period), Kind::Oscillator),
+                (0, 0) => (format!("xs{}", object.len()), Kind::StillLife),
+                _ => (format!("xq{}", period), Kind::Spaceship),
+            };
+
+            let code = phases
+                .iter()
+                .map(canonical)
+                .min_by_key(|code| shortest(code))
+                .unwrap();
+
+            return Classified {
+                name: format!("{}_{}", prefix, code),
+                kind,
+                period,
+            };
+        }
+
+        phases.push(world.alive_cells.clone());
+        world.next();
+    }
+
+    Classified {
+        name: format!("unknown_{}", canonical(object)),
+        kind: Kind::Unknown,
+        period: 0,
+    }
+}
+
+fn sparse(cells: Cells, rule: &Rule) -> SparseLife {
+    SparseLife {
+        alive_cells: cells,
+        rule: rule.clone(),
+        seed: 0,
+        viewport: Viewport::new(0, 0, 1, 1),
+        grid: Grid::new(1, 1),
+    }
+}
+
+fn shortest(code: &str) -> (usize, String) {
+    (code.len(), code.to_owned())
+}
+
+// The shortest encoding of the cells in any of the 8 orientations, earliest alphabetically.
+fn canonical(cells: &Cells) -> String {
+    // Flips in x and y, then whether x and y swap.
+    let orientations = [1, -1]
+        .iter()
+        .flat_map(|&sx| [1, -1].iter().map(move |&sy| (sx, sy)))
+        .flat_map(|(sx, sy)| [false, true].iter().map(move |&swap| (sx, sy, swap)));
+
+    orientations
+        .map(|(sx, sy, swap)| {
+            wechsler(
+                &cells
+                    .iter()
+                    .map(|&(x, y)| {
+                        if swap {
+                            (sx * y, sy * x)
+                        } else {
+                            (sx * x, sy * y)
+                        }
+                    })
+                    .collect(),
+            )
+        })
+        .min_by_key(|code| shortest(code))
+        .unwrap()
+}
+
+// Extended Wechsler format: each strip of 5 rows is written as one character per column,
+// with the top row as the lowest bit, and strips are separated by z. Runs of empty columns
+// are shortened to w (2), x (3) or y and a digit (4 or more), and dropped at the end of a strip.
+fn wechsler(cells: &Cells) -> String {
+    let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let width = cells.iter().map(|&(x, _)| x - left + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|&(_, y)| y - top + 1).max().unwrap_or(0);
+
+    let strips: Vec<String> = (0..(height + 4) / 5)
+        .map(|strip| {
+            let mut columns: Vec<usize> = (0..width)
+                .map(|x| {
+                    (0..5)
+                        .filter(|row| cells.contains(&(left + x, top + strip * 5 + row)))
+                        .map(|row| 1 << row)
+                        .sum()
+                })
+                .collect();
+
+            while columns.last() == Some(&0) {
+                columns.pop();
+            }
+
+            let mut encoded = String::new();
+            let mut zeros = 0;
+
+            for column in columns {
+                if column == 0 {
+                    zeros += 1;
+                    continue;
+                }
+
+                encoded.push_str(&empty_columns(zeros));
+                encoded.push(DIGITS[column] as char);
+                zeros = 0;
+            }
+
+            encoded
+        })
+        .collect();
+
+    strips.join("z")
+}
+
+fn empty_columns(mut zeros: usize) -> String {
+    let mut encoded = String::new();
+
+    while zeros > 0 {
+        let run = zeros.min(4 + DIGITS.len() - 1);
+
+        match run {
+            1 => encoded.push('0'),
+            2 => encoded.push('w'),
+            3 => encoded.push('x'),
+            _ => {
+                encoded.push('y');
+                encoded.push(DIGITS[run - 4] as char);
+            }
+        }
+
+        zeros -= run;
+    }
+
+    encoded
+}
+
+fn common_name(code: &str) -> Option<&'static str> {
+    match code {
+        "xs4_33" => Some("block"),
+        "xs4_252" => Some("tub"),
+        "xs5_253" => Some("boat"),
+        "xs6_356" => Some("ship"),
+        "xs6_696" => Some("beehive"),
+        "xs6_25a4" => Some("barge"),
+        "xs6_bd" => Some("snake"),
+        "xs7_2596" => Some("loaf"),
+        "xs7_25ac" => Some("long boat"),
+        "xs7_178c" => Some("eater"),
+        "xs8_69ic" => Some("mango"),
+        "xs8_6996" => Some("pond"),
+        "xp2_7" => Some("blinker"),
+        "xp2_7e" => Some("toad"),
+        "xp2_318c" => Some("beacon"),
+        "xq4_153" => Some("glider"),
+        "xq4_6frc" => Some("lightweight spaceship"),
+        "xq4_27dee6" => Some("middleweight spaceship"),
+        "xq4_27deee6" => Some("heavyweight spaceship"),
+        _ => None,
+    }
+}
+
+fn join(seeds: &[u32]) -> String {
+    seeds
+        .iter()
+        .map(|seed| seed.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(cells: &[(i64, i64)]) -> Cells {
+        cells.iter().copied().collect()
+    }
+
+    fn name(object: &[(i64, i64)]) -> (String, Kind) {
+        let object = classify(&cells(object), &Rule::conway());
+
+        (object.name, object.kind)
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn objects_are_named_by_apgcode() {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        let beehive = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)];
+        let blinker = [(0, 0), (1, 0), (2, 0)];
+        let beacon = [(0, 0), (1, 0), (0, 1), (3, 2), (2, 3), (3, 3)];
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let lwss = [
+            (1, 0),                 (4, 0),
+            (0, 1),
+            (0, 2),                 (4, 2),
+            (0, 3), (1, 3), (2, 3), (3, 3),
+        ];
+
+        assert_eq!(name(&block), ("xs4_33".to_owned(), Kind::StillLife));
+        assert_eq!(name(&beehive), ("xs6_696".to_owned(), Kind::StillLife));
+        assert_eq!(name(&blinker), ("xp2_7".to_owned(), Kind::Oscillator));
+        assert_eq!(name(&beacon), ("xp2_318c".to_owned(), Kind::Oscillator));
+        assert_eq!(name(&glider), ("xq4_153".to_owned(), Kind::Spaceship));
+        assert_eq!(name(&lwss), ("xq4_6frc".to_owned(), Kind::Spaceship));
+    }
+
+    // The R-pentomino takes over 1000 generations to settle.
+    #[test]
+    fn objects_which_dont_repeat_are_unknown() {
+        let r_pentomino = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+        assert_eq!(name(&r_pentomino).1, Kind::Unknown);
+    }
+
+    #[test]
+    fn wechsler_shortens_empty_columns() {
+        let spaced = |gap: i64| cells(&[(0, 0), (gap + 1, 0)]);
+
+        assert_eq!(wechsler(&spaced(1)), "101");
+        assert_eq!(wechsler(&spaced(2)), "1w1");
+        assert_eq!(wechsler(&spaced(3)), "1x1");
+        assert_eq!(wechsler(&spaced(4)), "1y01");
+        assert_eq!(wechsler(&spaced(14)), "1ya1");
+        assert_eq!(wechsler(&cells(&[(0, 0), (0, 5), (1, 6)])), "1z12");
+    }
+
+    #[test]
+    fn cells_within_the_gap_are_one_object() {
+        let objects = separate(
+            &cells(&[(0, 0), (2, 2), (5, 2), (20, 20)]),
+            Connectivity::MooreGap(GAP),
+        );
+
+        let mut sizes: Vec<usize> = objects.iter().map(|object| object.len()).collect();
+        sizes.sort_unstable();
+
+        assert_eq!(sizes, vec![1, 1, 2]);
+    }
+
+    /*
+
+       . . . . . . .      . . . . . . .
+       . * * . . . .      . * * . . . .
+       . * * . . . .      . * * . . . .
+       . . . . . . .  ->  . . . . . * .
+       . . . . * * *      . . . . . * .
+       . . . . . . .      . . . . . * .
+
+    */
+    #[test]
+    fn objects_close_together_are_counted_apart() {
+        let block_and_blinker = cells(&[(1, 1), (2, 1), (1, 2), (2, 2), (4, 4), (5, 4), (6, 4)]);
+
+        let objects = classify_all(&block_and_blinker, &Rule::conway());
+
+        assert_eq!(
+            objects,
+            vec![
+                ("xp2_7".to_owned(), Kind::Oscillator),
+                ("xs4_33".to_owned(), Kind::StillLife)
+            ]
+        );
+    }
+
+    // The toad falls apart into two pieces which die on their own every other generation.
+    #[test]
+    #[rustfmt::skip]
+    fn objects_which_fall_apart_stay_whole() {
+        let toad = cells(&[
+                    (2, 0),
+            (0, 1),         (3, 1),
+            (0, 2),         (3, 2),
+            (1, 3),
+        ]);
+
+        assert_eq!(
+            classify_all(&toad, &Rule::conway()),
+            vec![("xp2_7e".to_owned(), Kind::Oscillator)]
+        );
+    }
+
+    /*
+
+       . . . . . . . . . . . . . . . . .
+       . * * . . . . . * * * . . . . . .
+       . * * . . . . . . . . . . . . . .
+       . . . . . . . . . . . . . * . . .
+       . . . . . . . . . . . . . . * . .
+       . . . . . . . . . . . . * * * . .
+
+    */
+    #[test]
+    fn census_counts_objects_and_finds_rare_ones() {
+        let soup = |seed: u32| {
+            let mut cells = vec![(1, 1), (2, 1), (1, 2), (2, 2)];
+
+            if seed == 2 {
+                cells.extend(vec![(8, 1), (9, 1), (10, 1)]);
+            }
+            if seed == 3 {
+                cells.extend(vec![(13, 3), (14, 4), (12, 5), (13, 5), (14, 5)]);
+            }
+
+            Pattern::new(17, 6, cells)
+        };
+
+        let census = search(1..4, &Rule::conway(), 1000, 2, soup);
+
+        assert_eq!(census.soups, 3);
+        assert!(census.unsettled.is_empty());
+        assert_eq!(census.objects["xs4_33"].count, 3);
+        assert_eq!(census.objects["xs4_33"].seeds, vec![1, 2, 3]);
+
+        let rare: Vec<&str> = census
+            .rare(1)
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(rare, vec!["xp2_7", "xq4_153"]);
+
+        let report = census.report(1);
+        assert!(report.starts_with("3 soups, 3 settled"));
+        assert!(report.contains("xq4_153 (glider): 1 in seeds 3"));
+    }
+
+    #[test]
+    fn soups_which_dont_settle_are_reported() {
+        let r_pentomino = |_| Pattern::new(3, 3, vec![(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)]);
+
+        let census = search(7..8, &Rule::conway(), 100, 1, r_pentomino);
+
+        assert_eq!(census.unsettled, vec![7]);
+        assert!(census.report(1).ends_with("Didn't settle: seeds 7"));
+    }
+}
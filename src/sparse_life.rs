@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::grid::Grid;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::viewport::Viewport;
+use crate::world::Simulation;
+
+// A Life-like world on an unbounded plane, storing only the alive cells.
+// `grid` holds the cells visible through the viewport.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseLife {
+    pub alive_cells: HashSet<(i64, i64)>,
+    pub rule: Rule,
+    pub seed: u32,
+    pub viewport: Viewport,
+    pub grid: Grid,
+}
+
+impl Simulation for SparseLife {
+    fn seed(&self) -> &u32 {
+        &self.seed
+    }
+
+    fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn next(&mut self) {
+        let mut alive_neighbours_counts: HashMap<(i64, i64), usize> = HashMap::new();
+
+        for &(x, y) in self.alive_cells.iter() {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if dx != 0 || dy != 0 {
+                        *alive_neighbours_counts.entry((x + dx, y + dy)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut updated_cells: HashSet<(i64, i64)> = HashSet::new();
+
+        for &(x, y) in self.alive_cells.iter() {
+            let count = alive_neighbours_counts.get(&(x, y)).copied().unwrap_or(0);
+
+            if self.rule.is_alive(true, count) {
+                updated_cells.insert((x, y));
+            }
+        }
+
+        for (&position, &count) in alive_neighbours_counts.iter() {
+            if !self.alive_cells.contains(&position) && self.rule.is_alive(false, count) {
+                updated_cells.insert(position);
+            }
+        }
+
+        self.alive_cells = updated_cells;
+        self.grid = self.viewport.render(&self.alive_cells, &self.grid);
+    }
+
+    fn population(&self) -> u64 {
+        self.alive_cells.len() as u64
+    }
+
+    fn tracks_age(&self) -> bool {
+        false
+    }
+
+    fn view(&self, viewport: &Viewport) -> Grid {
+        let mut shifted = viewport.clone();
+        shifted.pan(self.viewport.x, self.viewport.y);
+
+        shifted.render(&self.alive_cells, &self.grid)
+    }
+
+    fn alive_positions(&self) -> Vec<(i64, i64)> {
+        self.alive_cells
+            .iter()
+            .map(|&(x, y)| (x - self.viewport.x, y - self.viewport.y))
+            .collect()
+    }
+
+    fn pattern(&self) -> Pattern {
+        Pattern::from_cells(
+            self.alive_cells.iter().copied().collect(),
+            Some(self.rule()),
+        )
+    }
+}
+
+impl SparseLife {
+    // Starts from the alive cells of a grid, viewed through a viewport of the same size.
+    pub fn from_grid(grid: Grid, rule: Rule, seed: u32) -> SparseLife {
+        let alive_cells = grid
+            .cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.alive)
+            .map(|cell| (cell.x as i64, cell.y as i64))
+            .collect();
+
+        let viewport = Viewport::new(0, 0, grid.cells[0].len() as u32, grid.cells.len() as u32);
+
+        SparseLife {
+            alive_cells,
+            rule,
+            seed,
+            viewport,
+            grid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    /*
+
+       * . .      . . .
+       * . .  ->  * * .  (and one more alive cell, left of the grid)
+       * . .      . . .
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn cells_are_born_past_the_edges_of_the_viewport() {
+        let grid = Grid::new_alive_grid(
+            3, 3,
+            String::from("."), String::from("x"), String::from("*"),
+            vec![(0, 0), (0, 1), (0, 2)],
+            vec![],
+        );
+
+        let mut world = SparseLife::from_grid(grid, Rule::conway(), 0);
+
+        world.next();
+
+        let expected_cells: HashSet<(i64, i64)> = vec![(-1, 1), (0, 1), (1, 1)].into_iter().collect();
+
+        assert_eq!(world.alive_cells, expected_cells);
+        assert_eq!(
+            world.grid.display(),
+            ". . .
+* * .
+. . ."
+        );
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn glider_keeps_travelling_past_the_viewport() {
+        let grid = Grid::new_alive_grid(
+            4, 4,
+            String::new(), String::new(), String::new(),
+            vec![
+                        (1, 0),
+                                (2, 1),
+                (0, 2), (1, 2), (2, 2),
+            ],
+            vec![],
+        );
+
+        let mut world = SparseLife::from_grid(grid, Rule::conway(), 0);
+
+        for _ in 0..40 {
+            world.next();
+        }
+
+        let expected_cells: HashSet<(i64, i64)> = vec![
+            (11, 10),
+            (12, 11),
+            (10, 12), (11, 12), (12, 12),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(world.alive_cells, expected_cells);
+    }
+
+    #[test]
+    fn unbounded_worlds_reject_b0_rules() {
+        let world = |ruleset| World::unbounded(ruleset, Grid::new(3, 3), 0);
+
+        assert!(world("B03/S23").is_err());
+        assert!(world("B0/S8").is_err());
+        assert!(world("B3/S23").is_ok());
+        assert!(world("game_of_life").is_ok());
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::objects::{self, Connectivity};
+use crate::viewport::Bounds;
+use crate::world::Simulation;
+
+// The numbers recorded for each generation. Births and deaths are counted since the
+// generation recorded before, dying cells are those of Generations rules, e.g. Brian's Brain,
+// and objects are groups of alive cells next to each other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    pub generation: u64,
+    pub population: u64,
+    pub births: u64,
+    pub deaths: u64,
+    pub bounds: Option<Bounds>,
+    pub dying: u64,
+    pub objects: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatsFormat {
+    Csv,
+    Json,
+}
+
+impl StatsFormat {
+    pub fn from_extension(path: &str) -> Option<StatsFormat> {
+        match Path::new(path).extension()?.to_str()? {
+            "csv" => Some(StatsFormat::Csv),
+            "json" => Some(StatsFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// Works out the statistics of each generation from the one before.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatsCollector {
+    previous: Option<HashSet<(i64, i64)>>,
+}
+
+impl StatsCollector {
+    pub fn new() -> StatsCollector {
+        StatsCollector::default()
+    }
+
+    // Forgets the generation before, e.g. when a new world starts,
+    // so the next one is counted as having no births or deaths.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn collect(&mut self, generation: u64, game: &dyn Simulation) -> Statistics {
+        let alive: HashSet<(i64, i64)> = game.alive_positions().into_iter().collect();
+
+        let (births, deaths) = match self.previous {
+            Some(ref previous) => (
+                alive.difference(previous).count() as u64,
+                previous.difference(&alive).count() as u64,
+            ),
+            None => (0, 0),
+        };
+
+        let statistics = Statistics {
+            generation,
+            population: game.population(),
+            births,
+            deaths,
+            bounds: game.bounds(),
+            dying: game
+                .grid()
+                .cells
+                .iter()
+                .flatten()
+                .filter(|cell| cell.dying)
+                .count() as u64,
+            objects: objects::label_world(game, Connectivity::Moore).len() as u64,
+        };
+
+        self.previous = Some(alive);
+
+        statistics
+    }
+}
+
+// Writes the statistics of each generation to a CSV or JSON file as the world runs.
+// A JSON file holds an array of objects, which is closed by `finish`. Exiting the process
+// doesn't drop the writer, so every way a run ends must finish it first.
+pub struct StatsWriter {
+    collector: StatsCollector,
+    format: StatsFormat,
+    out: Box<dyn Write>,
+    written: u64,
+    finished: bool,
+}
+
+impl StatsWriter {
+    pub fn create(path: &str) -> Result<StatsWriter, String> {
+        let format = StatsFormat::from_extension(path)
+            .ok_or_else(|| format!("Statistics file must end in .csv or .json: {}", path))?;
+
+        let file = File::create(path)
+            .map_err(|e| format!("Couldn't create statistics file {}: {}", path, e))?;
+
+        let mut writer = StatsWriter::new(Box::new(BufWriter::new(file)), format);
+
+        writer
+            .start()
+            .map_err(|e| format!("Couldn't write to statistics file {}: {}", path, e))?;
+
+        Ok(writer)
+    }
+
+    fn new(out: Box<dyn Write>, format: StatsFormat) -> StatsWriter {
+        StatsWriter {
+            collector: StatsCollector::new(),
+            format,
+            out,
+            written: 0,
+            finished: false,
+        }
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        match self.format {
+            StatsFormat::Csv => writeln!(
+                self.out,
+                "generation,population,births,deaths,left,top,right,bottom,dying,objects"
+            ),
+            StatsFormat::Json => writeln!(self.out, "["),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.collector.reset();
+    }
+
+    // Counts births and deaths from this world on without writing it, e.g. after stepping back.
+    pub fn resume(&mut self, game: &dyn Simulation) {
+        self.collector.collect(0, game);
+    }
+
+    pub fn record(&mut self, generation: u64, game: &dyn Simulation) -> Result<(), String> {
+        let statistics = self.collector.collect(generation, game);
+
+        self.write(&statistics)
+            .map_err(|e| format!("Couldn't write statistics: {}", e))
+    }
+
+    // Closes the JSON array. Finishing again does nothing.
+    pub fn finish(&mut self) -> Result<(), String> {
+        if self.finished {
+            return Ok(());
+        }
+
+        self.finished = true;
+
+        if self.format == StatsFormat::Json {
+            let end = if self.written > 0 { "\n]\n" } else { "]\n" };
+
+            self.out
+                .write_all(end.as_bytes())
+                .and_then(|_| self.out.flush())
+                .map_err(|e| format!("Couldn't write statistics: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, s: &Statistics) -> io::Result<()> {
+        match self.format {
+            StatsFormat::Csv => {
+                let bounds = match s.bounds {
+                    Some((left, top, right, bottom)) => {
+                        format!("{},{},{},{}", left, top, right, bottom)
+                    }
+                    None => ",,,".to_owned(),
+                };
+
+                writeln!(
+                    self.out,
+                    "{},{},{},{},{},{},{}",
+                    s.generation, s.population, s.births, s.deaths, bounds, s.dying, s.objects
+                )?;
+            }
+            StatsFormat::Json => {
+                let bounds = match s.bounds {
+                    Some((left, top, right, bottom)) => format!(
+                        "{{\"left\": {}, \"top\": {}, \"right\": {}, \"bottom\": {}}}",
+                        left, top, right, bottom
+                    ),
+                    None => "null".to_owned(),
+                };
+
+                if self.written > 0 {
+                    writeln!(self.out, ",")?;
+                }
+
+                write!(
+                    self.out,
+                    "  {{\"generation\": {}, \"population\": {}, \"births\": {}, \"deaths\": {}, \
+                     \"bounds\": {}, \"dying\": {}, \"objects\": {}}}",
+                    s.generation, s.population, s.births, s.deaths, bounds, s.dying, s.objects
+                )?;
+            }
+        }
+
+        self.written += 1;
+
+        // Flushed each generation, so the file can be read while the world runs.
+        self.out.flush()
+    }
+}
+
+impl Drop for StatsWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_worlds::world;
+    use crate::world::World;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Collects what's written, so it can be read while the writer is still in use.
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn write(format: StatsFormat, mut world: World, generations: u64) -> String {
+        let shared = Shared::default();
+
+        let mut writer = StatsWriter::new(Box::new(shared.clone()), format);
+        writer.start().unwrap();
+
+        for generation in 0..generations {
+            writer.record(generation, world.game.as_ref()).unwrap();
+            world.game.next();
+        }
+
+        writer.finish().unwrap();
+
+        // Kept, as exiting would, to check the file was closed without dropping the writer.
+        std::mem::forget(writer);
+
+        let bytes = shared.0.borrow().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    /*
+
+       . . . . .      . . . . .
+       . . * . .      . . . . .
+       . . * . .  ->  . * * * .
+       . . * . .      . . . . .
+
+    */
+    #[test]
+    fn blinker_has_two_births_and_two_deaths_each_generation() {
+        let blinker = world("game_of_life", vec![(2, 1), (2, 2), (2, 3)]);
+
+        assert_eq!(
+            write(StatsFormat::Csv, blinker, 2),
+            "generation,population,births,deaths,left,top,right,bottom,dying,objects
+0,3,0,0,2,1,2,3,0,1
+1,3,2,2,1,2,3,2,0,1
+"
+        );
+    }
+
+    #[test]
+    fn brians_brain_counts_dying_cells() {
+        let pair = world("brians_brain", vec![(1, 2), (2, 2)]);
+
+        assert_eq!(
+            write(StatsFormat::Json, pair, 2),
+            r#"[
+  {"generation": 0, "population": 2, "births": 0, "deaths": 0, "bounds": {"left": 1, "top": 2, "right": 2, "bottom": 2}, "dying": 0, "objects": 1},
+  {"generation": 1, "population": 4, "births": 4, "deaths": 2, "bounds": {"left": 1, "top": 1, "right": 2, "bottom": 3}, "dying": 2, "objects": 2}
+]
+"#
+        );
+    }
+
+    #[test]
+    fn empty_world_has_no_bounds() {
+        let empty = world("game_of_life", vec![]);
+
+        assert_eq!(
+            write(StatsFormat::Csv, empty, 1).lines().nth(1),
+            Some("0,0,0,0,,,,,0,0")
+        );
+        assert_eq!(
+            write(StatsFormat::Json, world("game_of_life", vec![]), 0),
+            "[\n]\n"
+        );
+    }
+
+    #[test]
+    fn finishing_twice_closes_the_array_once() {
+        let shared = Shared::default();
+        let mut writer = StatsWriter::new(Box::new(shared.clone()), StatsFormat::Json);
+        writer.start().unwrap();
+
+        writer.finish().unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        assert_eq!(
+            String::from_utf8(shared.0.borrow().clone()).unwrap(),
+            "[\n]\n"
+        );
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            StatsFormat::from_extension("run.csv"),
+            Some(StatsFormat::Csv)
+        );
+        assert_eq!(
+            StatsFormat::from_extension("a/run.json"),
+            Some(StatsFormat::Json)
+        );
+        assert_eq!(StatsFormat::from_extension("run.txt"), None);
+        assert!(StatsWriter::create("run.txt").is_err());
+    }
+}
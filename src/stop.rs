@@ -0,0 +1,207 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::cycle::Stabilised;
+
+// Exit codes of runs which can't go on: 1 for errors, and 2 for bad arguments,
+// the same as clap gives for arguments it can't parse.
+pub const FAILED: i32 = 1;
+pub const BAD_ARGUMENTS: i32 = 2;
+
+// Why a run ended. Each has its own exit code, after 1 for errors and 2 for bad arguments,
+// so scripts can tell them apart. Interrupted runs give 130, as shells do for Ctrl-C.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ending {
+    Generations,
+    Extinct,
+    Stabilised,
+    PopulationAbove(u64),
+    PopulationBelow(u64),
+    Timeout(Duration),
+    Interrupted,
+}
+
+impl Ending {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Ending::Generations => 0,
+            Ending::Extinct => 3,
+            Ending::Stabilised => 4,
+            Ending::PopulationAbove(_) | Ending::PopulationBelow(_) => 5,
+            Ending::Timeout(_) => 6,
+            Ending::Interrupted => 130,
+        }
+    }
+}
+
+impl fmt::Display for Ending {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ending::Generations => write!(f, "stopped after the last generation"),
+            Ending::Extinct => write!(f, "stopped as it died out"),
+            Ending::Stabilised => write!(f, "stopped as it stabilised"),
+            Ending::PopulationAbove(n) => write!(f, "stopped with population above {}", n),
+            Ending::PopulationBelow(n) => write!(f, "stopped with population below {}", n),
+            Ending::Timeout(t) => write!(f, "stopped after {}s", t.as_secs_f64()),
+            Ending::Interrupted => write!(f, "stopped when interrupted"),
+        }
+    }
+}
+
+// When a run stops before its last generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StopConditions {
+    pub extinction: bool,
+    pub stabilisation: bool,
+    pub population_above: Option<u64>,
+    pub population_below: Option<u64>,
+    pub timeout: Option<Duration>,
+}
+
+impl StopConditions {
+    // Extinction is checked first, so a world that dies out isn't also said to
+    // have stabilised or fallen below a population.
+    pub fn check(
+        &self,
+        population: u64,
+        stabilised: Option<Stabilised>,
+        elapsed: Duration,
+    ) -> Option<Ending> {
+        let extinct = population == 0 || stabilised.is_some_and(|s| s.extinct);
+
+        if extinct && (self.extinction || (self.stabilisation && stabilised.is_some())) {
+            Some(Ending::Extinct)
+        } else if self.stabilisation && stabilised.is_some() {
+            Some(Ending::Stabilised)
+        } else if let Some(n) = self.population_above.filter(|&n| population > n) {
+            Some(Ending::PopulationAbove(n))
+        } else if let Some(n) = self.population_below.filter(|&n| population < n) {
+            Some(Ending::PopulationBelow(n))
+        } else {
+            self.timeout.filter(|&t| elapsed >= t).map(Ending::Timeout)
+        }
+    }
+}
+
+// How many generations to run, where "forever" has no limit.
+pub fn parse_generations(generations: &str) -> Result<Option<u64>, String> {
+    match generations {
+        "forever" => Ok(None),
+        n => n
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Generations must be a number or forever, got: {}", n)),
+    }
+}
+
+// How many steps run for the given generations after generation 0, for engines stepping
+// several generations at once going on to the first step at or past the last generation.
+pub fn steps_for(generations: u64, generations_per_step: u64) -> u64 {
+    generations.div_ceil(generations_per_step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stabilised(extinct: bool) -> Option<Stabilised> {
+        Some(Stabilised {
+            generation: 10,
+            period: 1,
+            displacement: (0, 0),
+            extinct,
+        })
+    }
+
+    #[test]
+    fn nothing_stops_without_conditions() {
+        let conditions = StopConditions::default();
+
+        assert_eq!(
+            conditions.check(0, stabilised(true), Duration::from_secs(1000)),
+            None
+        );
+    }
+
+    #[test]
+    fn extinction_is_checked_before_stabilisation() {
+        let stable = StopConditions {
+            stabilisation: true,
+            ..StopConditions::default()
+        };
+        let extinction = StopConditions {
+            extinction: true,
+            ..StopConditions::default()
+        };
+        let zero = Duration::from_secs(0);
+
+        assert_eq!(stable.check(0, None, zero), None);
+        assert_eq!(
+            stable.check(0, stabilised(true), zero),
+            Some(Ending::Extinct)
+        );
+        assert_eq!(
+            stable.check(5, stabilised(false), zero),
+            Some(Ending::Stabilised)
+        );
+        assert_eq!(extinction.check(0, None, zero), Some(Ending::Extinct));
+        assert_eq!(extinction.check(5, stabilised(false), zero), None);
+    }
+
+    #[test]
+    fn population_crossing_a_threshold_or_timing_out_stops() {
+        let conditions = StopConditions {
+            population_above: Some(100),
+            population_below: Some(10),
+            timeout: Some(Duration::from_secs(30)),
+            ..StopConditions::default()
+        };
+        let zero = Duration::from_secs(0);
+
+        assert_eq!(conditions.check(50, None, zero), None);
+        assert_eq!(
+            conditions.check(101, None, zero),
+            Some(Ending::PopulationAbove(100))
+        );
+        assert_eq!(
+            conditions.check(9, None, zero),
+            Some(Ending::PopulationBelow(10))
+        );
+        assert_eq!(
+            conditions.check(50, None, Duration::from_secs(30)),
+            Some(Ending::Timeout(Duration::from_secs(30)))
+        );
+    }
+
+    #[test]
+    fn each_ending_has_its_own_exit_code() {
+        let codes: Vec<i32> = [
+            Ending::Generations,
+            Ending::Extinct,
+            Ending::Stabilised,
+            Ending::PopulationAbove(1),
+            Ending::Timeout(Duration::from_secs(1)),
+        ]
+        .iter()
+        .map(Ending::exit_code)
+        .collect();
+
+        assert_eq!(codes, vec![0, 3, 4, 5, 6]);
+        assert_eq!(Ending::PopulationBelow(1).exit_code(), 5);
+    }
+
+    #[test]
+    fn parse_generations_or_forever() {
+        assert_eq!(parse_generations("forever"), Ok(None));
+        assert_eq!(parse_generations("250"), Ok(Some(250)));
+        assert!(parse_generations("-1").is_err());
+    }
+
+    #[test]
+    fn steps_cover_every_generation() {
+        assert_eq!(steps_for(8, 1), 8);
+        assert_eq!(steps_for(0, 1), 0);
+        assert_eq!(steps_for(8, 4), 2);
+        assert_eq!(steps_for(9, 4), 3);
+    }
+}
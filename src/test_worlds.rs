@@ -0,0 +1,51 @@
+// Worlds and checks shared by the tests of several engines.
+use crate::grid::Grid;
+use crate::world::World;
+
+// A 6 by 6 world of the given ruleset and alive cells.
+#[rustfmt::skip]
+pub fn world(ruleset: &str, alive_cells: Vec<(u32, u32)>) -> World {
+    let grid = Grid::new_alive_grid(
+        6, 6,
+        String::new(), String::new(), String::new(),
+        alive_cells,
+        vec![],
+    );
+
+    World::from_ruleset(ruleset, grid, 0).unwrap()
+}
+
+// A 30 by 30 grid with alive cells scattered in a fixed pattern, busy enough for every
+// row band of a parallel step to hold births and deaths.
+pub fn scattered_grid() -> Grid {
+    let alive_cells = (0..30u32)
+        .flat_map(|y| (0..30u32).map(move |x| (x, y)))
+        .filter(|&(x, y)| (x * 7 + y * 13) % 5 < 2)
+        .collect();
+
+    Grid::new_alive_grid(
+        30,
+        30,
+        String::new(),
+        String::new(),
+        String::new(),
+        alive_cells,
+        vec![],
+    )
+}
+
+// Steps a copy of the world on several threads alongside the world itself,
+// checking they stay the same, ages and cells past the grid included.
+pub fn assert_parallel_matches_sequential(world: World) {
+    let mut sequential = world.clone();
+    let mut parallel = world;
+
+    for _ in 0..10 {
+        sequential.game.next();
+        parallel.game.next_parallel(4);
+
+        assert_eq!(sequential.game.grid(), parallel.game.grid());
+        assert_eq!(sequential.game.ages(), parallel.game.ages());
+        assert_eq!(sequential.game.pattern(), parallel.game.pattern());
+    }
+}
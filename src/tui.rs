@@ -0,0 +1,852 @@
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use rand::Rng;
+use std::cmp;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::colour::Colours;
+use crate::cycle::OnStable;
+use crate::editor::{Brush, Editor};
+use crate::grid::RenderMode;
+use crate::pattern::{self, Format, Pattern};
+use crate::progress::Progress;
+use crate::renderer::{self, Renderer};
+use crate::stats::StatsWriter;
+use crate::viewport::View;
+use crate::world::World;
+
+const HELP: &str = "space: pause  n: step  b: step back  +/-: speed  arrows: pan  [/]: zoom  \
+     f: follow  r: reseed  s: snapshot  e: edit  q: quit";
+
+// Rows below the world, for the status, help and message lines, and the cursor.
+const RESERVED_ROWS: u16 = 4;
+
+const EDIT_HELP: &str = "arrows: move  space: cell  p: paste  tab: next preset  c: clear  \
+     w: save  enter: start  esc: cancel  mouse: left draws, right erases";
+
+// How many earlier generations are kept to step back to.
+const HISTORY_LENGTH: usize = 100;
+
+const MIN_GEN_LENGTH: Duration = Duration::from_millis(10);
+const MAX_GEN_LENGTH: Duration = Duration::from_millis(5000);
+
+type Build<'a> = Box<dyn Fn(&Pattern, u32) -> Result<World, String> + 'a>;
+
+// An interactive view of a world, controlled from the keyboard and mouse.
+// `build` makes a world from a pattern and seed, and `soup` makes a random pattern from a seed.
+pub struct Tui<'a> {
+    world: World,
+    history: VecDeque<(World, u64)>,
+    progress: Progress,
+    paused: bool,
+    editor: Option<Editor>,
+    gen_length: Duration,
+    threads: usize,
+    render_mode: RenderMode,
+    colours: Option<Colours>,
+    view: View,
+    on_stable: OnStable,
+    drawing_file: String,
+    message: Option<String>,
+    renderer: Renderer,
+    build: Build<'a>,
+    soup: Box<dyn Fn(u32) -> Pattern + 'a>,
+}
+
+impl<'a> Tui<'a> {
+    pub fn new<B, S>(
+        world: World,
+        gen_length: Duration,
+        threads: usize,
+        build: B,
+        soup: S,
+    ) -> Tui<'a>
+    where
+        B: Fn(&Pattern, u32) -> Result<World, String> + 'a,
+        S: Fn(u32) -> Pattern + 'a,
+    {
+        Tui {
+            world,
+            history: VecDeque::new(),
+            progress: Progress::new(vec![], "snapshot.rle".to_owned(), None),
+            paused: false,
+            editor: None,
+            gen_length,
+            threads,
+            render_mode: RenderMode::Text,
+            colours: None,
+            view: View::new(1, false),
+            on_stable: OnStable::Continue,
+            drawing_file: "drawing.rle".to_owned(),
+            message: None,
+            renderer: Renderer::new(),
+            build: Box::new(build),
+            soup: Box::new(soup),
+        }
+    }
+
+    pub fn with_snapshots(self, snapshot_at: Vec<u64>, snapshot_file: String) -> Tui<'a> {
+        Tui {
+            progress: Progress {
+                snapshot_at,
+                snapshot_file,
+                ..self.progress
+            },
+            ..self
+        }
+    }
+
+    pub fn with_render_mode(self, render_mode: RenderMode) -> Tui<'a> {
+        Tui {
+            render_mode,
+            ..self
+        }
+    }
+
+    pub fn with_colours(self, colours: Option<Colours>) -> Tui<'a> {
+        Tui { colours, ..self }
+    }
+
+    pub fn with_view(self, view: View) -> Tui<'a> {
+        Tui { view, ..self }
+    }
+
+    pub fn with_on_stable(self, on_stable: OnStable) -> Tui<'a> {
+        Tui {
+            on_stable,
+            progress: self.progress.with_on_stable(on_stable),
+            ..self
+        }
+    }
+
+    pub fn with_stats(self, stats: Option<StatsWriter>) -> Tui<'a> {
+        Tui {
+            progress: Progress {
+                stats,
+                ..self.progress
+            },
+            ..self
+        }
+    }
+
+    // Starts in edit mode, drawing on the world shown.
+    pub fn editing(self, drawing_file: String) -> Tui<'a> {
+        let editor = Editor::new(self.world.game.grid().clone());
+
+        Tui {
+            paused: true,
+            editor: Some(editor),
+            drawing_file,
+            ..self
+        }
+    }
+
+    pub fn with_drawing_file(self, drawing_file: String) -> Tui<'a> {
+        Tui {
+            drawing_file,
+            ..self
+        }
+    }
+
+    // Runs until quit, then closes the statistics file however the run ended.
+    pub fn run(&mut self) -> Result<(), String> {
+        let ran = self.run_until_quit();
+
+        ran.and(self.progress.finish())
+    }
+
+    fn run_until_quit(&mut self) -> Result<(), String> {
+        let _terminal = RawTerminal::enter().map_err(|e| e.to_string())?;
+
+        self.save_snapshot();
+
+        // When editing, statistics start with the world drawn.
+        if self.editor.is_none() {
+            self.record_stats();
+        }
+
+        let mut next_step = Instant::now() + self.gen_length;
+
+        loop {
+            self.draw().map_err(|e| e.to_string())?;
+
+            let timeout = next_step.saturating_duration_since(Instant::now());
+
+            let event = if self.paused || event::poll(timeout).map_err(|e| e.to_string())? {
+                Some(event::read().map_err(|e| e.to_string())?)
+            } else {
+                self.step();
+                next_step = Instant::now() + self.gen_length;
+                None
+            };
+
+            match event {
+                Some(Event::Key(key)) if !self.handle_key(key) => return Ok(()),
+                Some(Event::Mouse(mouse)) => self.handle_mouse(mouse),
+                Some(Event::Resize(_, _)) => self.renderer.invalidate(),
+                _ => (),
+            }
+        }
+    }
+
+    // Returns false when the key quits.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.message = None;
+
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+
+        if self.editor.is_some() {
+            self.handle_edit_key(key);
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char(' ') | KeyCode::Char('p') => self.paused = !self.paused,
+            KeyCode::Char('n') => {
+                self.paused = true;
+                self.step();
+            }
+            KeyCode::Char('b') => {
+                self.paused = true;
+                self.step_back();
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.gen_length = cmp::max(self.gen_length / 2, MIN_GEN_LENGTH)
+            }
+            KeyCode::Char('-') => self.gen_length = cmp::min(self.gen_length * 2, MAX_GEN_LENGTH),
+            KeyCode::Left => self.pan(-1, 0),
+            KeyCode::Right => self.pan(1, 0),
+            KeyCode::Up => self.pan(0, -1),
+            KeyCode::Down => self.pan(0, 1),
+            KeyCode::Char('[') => self.zoom(self.view.zoom / 2),
+            KeyCode::Char(']') => self.zoom(self.view.zoom * 2),
+            KeyCode::Char('f') => self.view.follow = !self.view.follow,
+            KeyCode::Char('r') => self.reseed(rand::thread_rng().gen_range(1, 10000)),
+            KeyCode::Char('s') => self.snapshot(),
+            KeyCode::Char('e') => {
+                self.paused = true;
+                self.editor = Some(Editor::new(self.world.game.grid().clone()));
+            }
+            _ => (),
+        }
+
+        true
+    }
+
+    fn screen(&self) -> (u16, u16) {
+        self.view
+            .screen(self.world.game.grid(), self.render_mode, RESERVED_ROWS)
+    }
+
+    // Pans an eighth of the way across or down the screen.
+    fn pan(&mut self, dx: i64, dy: i64) {
+        let (columns, rows) = self.screen();
+        let (width, height) = self.view.size(columns, rows, self.render_mode);
+
+        self.view.pan(
+            dx * cmp::max(width as i64 / 8, 1),
+            dy * cmp::max(height as i64 / 8, 1),
+        );
+    }
+
+    fn zoom(&mut self, zoom: u32) {
+        let (columns, rows) = self.screen();
+
+        self.view.set_zoom(zoom, columns, rows, self.render_mode);
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) {
+        let editor = match self.editor.as_mut() {
+            Some(e) => e,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => editor.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => editor.move_cursor(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => editor.move_cursor(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => editor.move_cursor(0, 1),
+            KeyCode::Char(' ') => editor.cycle(),
+            KeyCode::Char('p') => editor.paste(),
+            KeyCode::Tab => editor.next_preset(),
+            KeyCode::Char('c') => editor.clear(),
+            KeyCode::Char('w') => self.save_drawing(),
+            KeyCode::Enter => self.start_from_drawing(),
+            KeyCode::Esc => self.editor = None,
+            _ => (),
+        }
+    }
+
+    // In edit mode, the left button draws alive cells and the right button erases them.
+    // Each cell is drawn as a character followed by a space.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let editor = match self.editor.as_mut() {
+            Some(e) => e,
+            None => return,
+        };
+
+        let brush =
+            match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left)
+                | MouseEventKind::Drag(MouseButton::Left) => Brush::Alive,
+                MouseEventKind::Down(MouseButton::Right)
+                | MouseEventKind::Drag(MouseButton::Right) => Brush::Dead,
+                _ => return,
+            };
+
+        let (x, y) = (mouse.column as u32 / 2, mouse.row as u32);
+
+        if x < editor.width() && y < editor.height() {
+            editor.cursor = (x, y);
+            editor.paint(x, y, brush);
+        }
+    }
+
+    fn save_drawing(&mut self) {
+        let editor = match self.editor.as_ref() {
+            Some(e) => e,
+            None => return,
+        };
+
+        let pattern = editor.pattern(Some(self.world.game.rule()));
+        let format = Format::for_writing(&self.drawing_file);
+
+        self.message = Some(match pattern::write(&pattern, format) {
+            Ok(contents) => match fs::write(&self.drawing_file, contents) {
+                Ok(_) => format!("Saved drawing to {}", self.drawing_file),
+                Err(e) => format!("Couldn't save drawing to {}: {}", self.drawing_file, e),
+            },
+            Err(e) => format!("Couldn't save drawing to {}: {}", self.drawing_file, e),
+        });
+    }
+
+    fn start_from_drawing(&mut self) {
+        let editor = match self.editor.as_ref() {
+            Some(e) => e,
+            None => return,
+        };
+
+        match (self.build)(&editor.pattern(None), *self.world.game.seed()) {
+            Ok(world) => {
+                self.restart(world);
+                self.editor = None;
+                self.paused = false;
+            }
+            Err(e) => self.message = Some(e),
+        }
+    }
+
+    fn restart(&mut self, world: World) {
+        self.world = world;
+        self.history.clear();
+        self.progress.restart();
+
+        self.record_stats();
+    }
+
+    fn step(&mut self) {
+        self.observe();
+
+        self.history
+            .push_back((self.world.clone(), self.progress.generation));
+
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+
+        self.progress.step(&mut self.world, self.threads);
+
+        self.save_snapshot();
+        self.record_stats();
+        self.observe();
+    }
+
+    // Checks whether the world has started repeating, and if it has, pauses or reseeds
+    // when asked to.
+    fn observe(&mut self) {
+        let stabilised = match self.progress.observe(&self.world) {
+            Some(s) => s,
+            None => return,
+        };
+
+        match self.on_stable {
+            OnStable::Continue => self.message = Some(stabilised.to_string()),
+            OnStable::Stop => {
+                self.paused = true;
+                self.message = Some(stabilised.to_string());
+            }
+            OnStable::Reseed => {
+                self.reseed(rand::thread_rng().gen_range(1, 10000));
+                self.message = Some(format!("{}, so reseeded", stabilised));
+            }
+        }
+    }
+
+    fn step_back(&mut self) {
+        match self.history.pop_back() {
+            Some((world, generation)) => {
+                self.world = world;
+                self.progress.generation = generation;
+
+                if let Some(ref mut stats) = self.progress.stats {
+                    stats.resume(self.world.game.as_ref());
+                }
+            }
+            None => self.message = Some("No earlier generations to step back to".to_owned()),
+        }
+    }
+
+    fn reseed(&mut self, seed: u32) {
+        match (self.build)(&(self.soup)(seed), seed) {
+            Ok(world) => self.restart(world),
+            Err(e) => self.message = Some(e),
+        }
+    }
+
+    fn save_snapshot(&mut self) {
+        if let Err(e) = self.progress.save_snapshot(&self.world) {
+            self.message = Some(e);
+        }
+    }
+
+    // Saves the world shown now to the snapshot file, whatever its generation.
+    fn snapshot(&mut self) {
+        let pattern = self.world.game.pattern();
+
+        self.message = Some(
+            match pattern::save_snapshot(
+                &pattern,
+                &self.progress.snapshot_file,
+                self.progress.generation,
+            ) {
+                Ok(path) => format!("Saved snapshot to {}", path.display()),
+                Err(e) => e,
+            },
+        );
+    }
+
+    fn record_stats(&mut self) {
+        if let Err(e) = self.progress.record_stats(&self.world) {
+            self.message = Some(e);
+        }
+    }
+
+    fn status(&self) -> String {
+        match self.editor {
+            Some(ref editor) => format!(
+                "editing; cursor = ({}, {}); preset = {}",
+                editor.cursor.0,
+                editor.cursor.1,
+                editor.preset_key()
+            ),
+            None => format!(
+                "seed = {}; generation = {}; population = {}; rule = {}; {} ({}ms per generation){}",
+                self.world.game.seed(),
+                self.progress.generation,
+                self.world.game.population(),
+                self.world.game.rule(),
+                if self.paused { "paused" } else { "running" },
+                self.gen_length.as_millis(),
+                self.progress
+                    .detector
+                    .stabilised()
+                    .map_or(String::new(), |s| format!("; {}", s))
+            ),
+        }
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        // The editor draws in text, so the cursor is on a single cell.
+        let colours = self.colours.as_ref();
+
+        let (mut lines, highlight) = match self.editor {
+            Some(ref editor) => {
+                let (x, y) = editor.cursor;
+
+                // Each cell is drawn followed by a space.
+                let column = editor.grid.cells[y as usize][..x as usize]
+                    .iter()
+                    .map(|cell| cell.display().chars().count() + 1)
+                    .sum();
+
+                (
+                    editor.grid.glyphs(RenderMode::Text, colours),
+                    Some((column, y as usize)),
+                )
+            }
+            None => {
+                let (columns, rows) = self.screen();
+                let game = self.world.game.as_ref();
+
+                self.view.track(game, columns, rows, self.render_mode);
+
+                (
+                    self.view
+                        .glyphs(game, columns, rows, self.render_mode, colours),
+                    None,
+                )
+            }
+        };
+
+        lines.push(renderer::plain(&self.status()));
+        lines.push(renderer::plain(if self.editor.is_some() {
+            EDIT_HELP
+        } else {
+            HELP
+        }));
+
+        if let Some(ref message) = self.message {
+            lines.push(renderer::plain(message));
+        }
+
+        // Raw mode doesn't return to the start of the line after a newline,
+        // so each line is drawn at its own position.
+        self.renderer.render(&mut io::stdout(), &lines, highlight)
+    }
+}
+
+// Puts the terminal in raw mode on an alternate screen with mouse reporting,
+// restoring it when dropped, including when the program panics.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, Hide)?;
+
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[rustfmt::skip]
+    fn blinker() -> World {
+        let grid = Grid::new_alive_grid(
+            3, 3,
+            String::new(), String::new(), String::new(),
+            vec![(0, 1), (1, 1), (2, 1)],
+            vec![],
+        );
+
+        World::from_ruleset("game_of_life", grid, 7).unwrap()
+    }
+
+    fn tui() -> Tui<'static> {
+        Tui::new(
+            blinker(),
+            Duration::from_millis(250),
+            1,
+            |pattern: &Pattern, seed| {
+                World::from_ruleset("game_of_life", pattern.to_grid(".", "x", "*"), seed)
+            },
+            |_| Pattern::new(3, 3, vec![]),
+        )
+    }
+
+    fn press(tui: &mut Tui, code: KeyCode) -> bool {
+        tui.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn step_then_step_back_restores_the_world() {
+        let mut tui = tui();
+        let starting_grid = tui.world.game.grid().clone();
+
+        press(&mut tui, KeyCode::Char('n'));
+
+        assert!(tui.paused);
+        assert_eq!(tui.progress.generation, 1);
+        assert_ne!(tui.world.game.grid(), &starting_grid);
+
+        press(&mut tui, KeyCode::Char('b'));
+
+        assert_eq!(tui.progress.generation, 0);
+        assert_eq!(tui.world.game.grid(), &starting_grid);
+
+        press(&mut tui, KeyCode::Char('b'));
+
+        assert_eq!(tui.progress.generation, 0);
+        assert!(tui.message.is_some());
+    }
+
+    #[test]
+    fn history_is_limited() {
+        let mut tui = tui();
+
+        for _ in 0..HISTORY_LENGTH + 10 {
+            tui.step();
+        }
+
+        assert_eq!(tui.history.len(), HISTORY_LENGTH);
+        assert_eq!(tui.history.front().unwrap().1, 10);
+    }
+
+    #[test]
+    fn speed_stays_within_limits() {
+        let mut tui = tui();
+
+        for _ in 0..20 {
+            press(&mut tui, KeyCode::Char('+'));
+        }
+
+        assert_eq!(tui.gen_length, MIN_GEN_LENGTH);
+
+        for _ in 0..20 {
+            press(&mut tui, KeyCode::Char('-'));
+        }
+
+        assert_eq!(tui.gen_length, MAX_GEN_LENGTH);
+    }
+
+    #[test]
+    fn arrows_pan_and_brackets_zoom() {
+        let mut tui = tui();
+
+        press(&mut tui, KeyCode::Char('f'));
+        assert!(tui.view.follow);
+
+        press(&mut tui, KeyCode::Right);
+        press(&mut tui, KeyCode::Down);
+        press(&mut tui, KeyCode::Down);
+
+        assert_eq!((tui.view.x, tui.view.y), (1, 2));
+        assert!(!tui.view.follow);
+        assert_eq!(tui.progress.generation, 0);
+
+        press(&mut tui, KeyCode::Char(']'));
+        assert_eq!(tui.view.zoom, 2);
+
+        press(&mut tui, KeyCode::Char('['));
+        press(&mut tui, KeyCode::Char('['));
+        assert_eq!(tui.view.zoom, 1);
+    }
+
+    #[test]
+    fn stops_once_the_world_repeats() {
+        let mut tui = tui().with_on_stable(OnStable::Stop);
+
+        tui.step();
+        assert!(!tui.paused);
+
+        tui.step();
+        assert!(tui.paused);
+        assert_eq!(
+            tui.message.as_deref(),
+            Some("stabilised at generation 0 with period 2")
+        );
+        assert!(tui
+            .status()
+            .ends_with("; stabilised at generation 0 with period 2"));
+
+        tui.reseed(3);
+        assert_eq!(tui.progress.detector.stabilised(), None);
+    }
+
+    #[test]
+    fn reseeds_once_the_world_dies_out() {
+        let mut tui = tui().with_on_stable(OnStable::Reseed);
+        tui.reseed(1);
+
+        tui.step();
+
+        assert_eq!(tui.progress.generation, 0);
+        assert_eq!(
+            tui.message.as_deref(),
+            Some("died out at generation 0, so reseeded")
+        );
+    }
+
+    #[test]
+    fn pause_reseed_and_quit() {
+        let mut tui = tui();
+        tui.step();
+
+        assert!(press(&mut tui, KeyCode::Char(' ')));
+        assert!(tui.paused);
+        assert!(press(&mut tui, KeyCode::Char(' ')));
+        assert!(!tui.paused);
+
+        press(&mut tui, KeyCode::Char('r'));
+
+        assert_eq!(tui.world.game.population(), 0);
+        assert_eq!(tui.progress.generation, 0);
+
+        tui.step();
+        tui.reseed(42);
+
+        assert_eq!(*tui.world.game.seed(), 42);
+        assert_eq!(tui.progress.generation, 0);
+        assert!(tui.history.is_empty());
+
+        assert!(!press(&mut tui, KeyCode::Char('q')));
+        assert!(!tui.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn draw_in_edit_mode_then_start() {
+        let mut tui = tui();
+
+        press(&mut tui, KeyCode::Char('e'));
+
+        assert!(tui.paused);
+        assert_eq!(tui.editor.as_ref().unwrap().cursor, (1, 1));
+
+        press(&mut tui, KeyCode::Char('c'));
+        press(&mut tui, KeyCode::Up);
+        press(&mut tui, KeyCode::Char(' '));
+        press(&mut tui, KeyCode::Down);
+        press(&mut tui, KeyCode::Char(' '));
+
+        tui.handle_mouse(MouseEvent {
+            kind: MouseEventKind::Drag(MouseButton::Left),
+            column: 5,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        });
+
+        // Keys go to the editor, so 'q' doesn't quit.
+        assert!(press(&mut tui, KeyCode::Char('q')));
+
+        press(&mut tui, KeyCode::Enter);
+
+        assert!(tui.editor.is_none());
+        assert!(!tui.paused);
+        assert_eq!(tui.progress.generation, 0);
+        assert_eq!(*tui.world.game.seed(), 7);
+        assert_eq!(
+            tui.world.game.pattern().alive_cells,
+            vec![(1, 0), (2, 0), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn cancel_editing_keeps_the_world() {
+        let mut tui = tui();
+        let starting_grid = tui.world.game.grid().clone();
+
+        press(&mut tui, KeyCode::Char('e'));
+        press(&mut tui, KeyCode::Char('c'));
+        press(&mut tui, KeyCode::Esc);
+
+        assert!(tui.editor.is_none());
+        assert_eq!(tui.world.game.grid(), &starting_grid);
+    }
+
+    #[test]
+    fn snapshot_saves_the_generation_shown() {
+        let dir = std::env::temp_dir().join(format!("tui_snapshot_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("blinker.cells");
+
+        let mut tui = tui().with_snapshots(vec![], file.to_str().unwrap().to_owned());
+
+        press(&mut tui, KeyCode::Char('n'));
+        press(&mut tui, KeyCode::Char('s'));
+
+        let saved = dir.join("blinker_1.cells");
+        assert_eq!(
+            tui.message,
+            Some(format!("Saved snapshot to {}", saved.display()))
+        );
+        assert_eq!(
+            pattern::read(saved.to_str().unwrap()).unwrap().alive_cells,
+            tui.world.game.pattern().alive_cells
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Plain runs record a generation, then step, from generation 0 until they've run for
+    // --generations, so running for 4 should save what 4 steps of the terminal UI do.
+    #[test]
+    fn plain_runs_number_generations_like_the_tui() {
+        let dir = std::env::temp_dir().join(format!("tui_numbering_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_owned();
+
+        let shown = {
+            let mut tui = tui()
+                .with_snapshots(vec![0, 3], path("tui.cells"))
+                .with_stats(Some(StatsWriter::create(&path("tui.csv")).unwrap()));
+
+            tui.save_snapshot();
+            tui.record_stats();
+
+            for _ in 0..4 {
+                tui.step();
+            }
+
+            tui.message.take()
+        };
+
+        let stabilised = {
+            let mut world = blinker();
+            let mut progress = Progress::new(
+                vec![0, 3],
+                path("plain.cells"),
+                Some(StatsWriter::create(&path("plain.csv")).unwrap()),
+            );
+            let mut stabilised = None;
+
+            for step in 0..=4 {
+                stabilised = stabilised.or(progress.observe(&world));
+                progress.save_snapshot(&world).unwrap();
+                progress.record_stats(&world).unwrap();
+
+                if step < 4 {
+                    progress.step(&mut world, 1);
+                }
+            }
+
+            stabilised
+        };
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+
+        assert_eq!(read("tui.csv"), read("plain.csv"));
+        assert_eq!(
+            read("tui.csv").lines().nth(1).unwrap().split(',').next(),
+            Some("0")
+        );
+        assert_eq!(
+            read("tui.csv").lines().last().unwrap().split(',').next(),
+            Some("4")
+        );
+        assert_eq!(read("tui_0.cells"), read("plain_0.cells"));
+        assert_eq!(read("tui_3.cells"), read("plain_3.cells"));
+        assert_eq!(shown, stabilised.map(|s| s.to_string()));
+        assert_eq!(
+            shown.as_deref(),
+            Some("stabilised at generation 0 with period 2")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::HashSet;
+
+use crate::cell::Cell;
+use crate::colour::Colours;
+use crate::grid::{Grid, RenderMode};
+use crate::renderer::Glyph;
+use crate::world::Simulation;
+
+// The smallest box holding every alive cell, as (left, top, right, bottom), inclusive.
+pub type Bounds = (i64, i64, i64, i64);
+
+// The most cells across or down one character can show.
+pub const MAX_ZOOM: u32 = 64;
+
+// A window onto an unbounded world, with signed coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: i64,
+    pub y: i64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn new(x: i64, y: i64, width: u32, height: u32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn pan(&mut self, dx: i64, dy: i64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    // The cells of a grid inside the viewport, with those past its edges dead.
+    pub fn crop(&self, grid: &Grid) -> Grid {
+        let mut cropped = self.render_with(|_, _| false, grid);
+
+        for row in cropped.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                let x = self.x + cell.x as i64;
+                let y = self.y + cell.y as i64;
+
+                if x >= 0 && y >= 0 {
+                    if let Some(original) = grid
+                        .cells
+                        .get(y as usize)
+                        .and_then(|row| row.get(x as usize))
+                    {
+                        *cell = Cell {
+                            x: cell.x,
+                            y: cell.y,
+                            ..original.clone()
+                        };
+                    }
+                }
+            }
+        }
+
+        cropped
+    }
+
+    // Renders the alive cells inside the viewport as a Grid,
+    // using the characters of the given template grid.
+    pub fn render(&self, alive_cells: &HashSet<(i64, i64)>, template: &Grid) -> Grid {
+        self.render_with(|x, y| alive_cells.contains(&(x, y)), template)
+    }
+
+    pub fn render_with<F>(&self, is_alive: F, template: &Grid) -> Grid
+    where
+        F: Fn(i64, i64) -> bool,
+    {
+        let cell = &template.cells[0][0];
+
+        let cells = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let updated_cell = Cell::new_with_characters(
+                            x,
+                            y,
+                            cell.dead_character.clone(),
+                            cell.dying_character.clone(),
+                            cell.alive_character.clone(),
+                        );
+
+                        if is_alive(self.x + x as i64, self.y + y as i64) {
+                            updated_cell.set_alive()
+                        } else {
+                            updated_cell
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Grid {
+            cells,
+            topology: template.topology,
+        }
+    }
+}
+
+pub fn bounds<I>(alive_cells: I) -> Option<Bounds>
+where
+    I: IntoIterator<Item = (i64, i64)>,
+{
+    alive_cells
+        .into_iter()
+        .fold(None, |bounds, (x, y)| match bounds {
+            None => Some((x, y, x, y)),
+            Some((left, top, right, bottom)) => {
+                Some((left.min(x), top.min(y), right.max(x), bottom.max(y)))
+            }
+        })
+}
+
+// What part of a world is drawn on a terminal of columns by rows characters: the cell at
+// the top left, in the coordinates of `Simulation::view`, and how many cells across and down
+// each character shows. Zoomed out, each zoom by zoom block of cells is drawn as one
+// character, shaded by how many of them are alive, followed by a space.
+// Following keeps the alive cells in the middle as they move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    pub x: i64,
+    pub y: i64,
+    pub zoom: u32,
+    pub follow: bool,
+}
+
+impl View {
+    pub fn new(zoom: u32, follow: bool) -> View {
+        View {
+            x: 0,
+            y: 0,
+            zoom: zoom.clamp(1, MAX_ZOOM),
+            follow,
+        }
+    }
+
+    // How many cells across and down fit on the terminal.
+    pub fn size(&self, columns: u16, rows: u16, mode: RenderMode) -> (u32, u32) {
+        let (columns, rows) = (columns as u32, rows as u32);
+
+        match mode {
+            _ if self.zoom > 1 => (columns.div_ceil(2) * self.zoom, rows * self.zoom),
+            RenderMode::Text | RenderMode::Age => (columns.div_ceil(2), rows),
+            RenderMode::HalfBlock => (columns, rows * 2),
+            RenderMode::Braille => (columns * 2, rows * 4),
+        }
+    }
+
+    // How many columns and rows the terminal needs to show width by height cells.
+    pub fn characters(&self, width: u32, height: u32, mode: RenderMode) -> (u16, u16) {
+        let (columns, rows) = match mode {
+            _ if self.zoom > 1 => (width.div_ceil(self.zoom) * 2, height.div_ceil(self.zoom)),
+            RenderMode::Text | RenderMode::Age => (width * 2, height),
+            RenderMode::HalfBlock => (width, height.div_ceil(2)),
+            RenderMode::Braille => (width.div_ceil(2), height.div_ceil(4)),
+        };
+
+        (
+            columns.min(u16::MAX as u32) as u16,
+            rows.min(u16::MAX as u32) as u16,
+        )
+    }
+
+    // The columns and rows to draw in: as many as the grid takes unzoomed, but no more than
+    // the terminal has, less the reserved rows, when writing to one.
+    pub fn screen(&self, grid: &Grid, mode: RenderMode, reserved_rows: u16) -> (u16, u16) {
+        use crossterm::terminal;
+        use crossterm::tty::IsTty;
+        use std::io;
+
+        let width = grid.cells.first().map_or(0, |row| row.len()) as u32;
+        let (columns, rows) = View::new(1, false).characters(width, grid.cells.len() as u32, mode);
+
+        match terminal::size() {
+            Ok((terminal_columns, terminal_rows)) if io::stdout().is_tty() => (
+                columns.min(terminal_columns),
+                rows.min(terminal_rows.saturating_sub(reserved_rows)).max(1),
+            ),
+            _ => (columns, rows),
+        }
+    }
+
+    pub fn viewport(&self, columns: u16, rows: u16, mode: RenderMode) -> Viewport {
+        let (width, height) = self.size(columns, rows, mode);
+
+        Viewport::new(self.x, self.y, width, height)
+    }
+
+    // Moves by a number of cells, and stops following.
+    pub fn pan(&mut self, dx: i64, dy: i64) {
+        self.x += dx;
+        self.y += dy;
+        self.follow = false;
+    }
+
+    // Changes how many cells each character shows, keeping the same cell in the middle.
+    pub fn set_zoom(&mut self, zoom: u32, columns: u16, rows: u16, mode: RenderMode) {
+        let (width, height) = self.size(columns, rows, mode);
+        let centre = (self.x + width as i64 / 2, self.y + height as i64 / 2);
+
+        self.zoom = zoom.clamp(1, MAX_ZOOM);
+
+        self.centre_on(centre, columns, rows, mode);
+    }
+
+    pub fn centre_on(&mut self, (x, y): (i64, i64), columns: u16, rows: u16, mode: RenderMode) {
+        let (width, height) = self.size(columns, rows, mode);
+
+        self.x = x - width as i64 / 2;
+        self.y = y - height as i64 / 2;
+    }
+
+    // Zooms out until the whole box fits, and centres on it.
+    pub fn fit(&mut self, bounds: Bounds, columns: u16, rows: u16, mode: RenderMode) {
+        let (left, top, right, bottom) = bounds;
+
+        self.zoom = 1;
+
+        while self.zoom < MAX_ZOOM {
+            let (width, height) = self.size(columns, rows, mode);
+
+            if right - left < width as i64 && bottom - top < height as i64 {
+                break;
+            }
+
+            self.zoom += 1;
+        }
+
+        self.centre_on(middle(bounds), columns, rows, mode);
+    }
+
+    // Keeps the alive cells of the world in the middle, when following.
+    pub fn track(&mut self, game: &dyn Simulation, columns: u16, rows: u16, mode: RenderMode) {
+        if self.follow {
+            if let Some(bounds) = game.bounds() {
+                self.centre_on(middle(bounds), columns, rows, mode);
+            }
+        }
+    }
+
+    pub fn glyphs(
+        &self,
+        game: &dyn Simulation,
+        columns: u16,
+        rows: u16,
+        mode: RenderMode,
+        colours: Option<&Colours>,
+    ) -> Vec<Vec<Glyph>> {
+        let grid = game.view(&self.viewport(columns, rows, mode));
+
+        if self.zoom > 1 {
+            grid.density_glyphs(self.zoom as usize, colours)
+        } else {
+            grid.glyphs(mode, colours)
+        }
+    }
+}
+
+fn middle((left, top, right, bottom): Bounds) -> (i64, i64) {
+    ((left + right) / 2, (top + bottom) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_of_life::GameOfLife;
+
+    #[test]
+    fn render_only_cells_inside_viewport() {
+        let alive_cells: HashSet<(i64, i64)> =
+            vec![(-1, -1), (0, 0), (2, 1), (3, 0)].into_iter().collect();
+
+        let viewport = Viewport::new(-1, -1, 3, 3);
+
+        assert_eq!(
+            viewport.render(&alive_cells, &Grid::new(1, 1)).display(),
+            "* . .
+. * .
+. . ."
+        );
+    }
+
+    #[test]
+    fn panning_moves_rendered_cells() {
+        let alive_cells: HashSet<(i64, i64)> = vec![(5, 5)].into_iter().collect();
+
+        let mut viewport = Viewport::new(0, 0, 2, 1);
+        viewport.pan(4, 5);
+
+        assert_eq!(
+            viewport.render(&alive_cells, &Grid::new(1, 1)).display(),
+            ". *"
+        );
+    }
+
+    #[test]
+    fn crop_keeps_cells_inside_and_kills_cells_outside() {
+        let grid = Grid::new_alive_grid(
+            3,
+            2,
+            ".".to_owned(),
+            "x".to_owned(),
+            "*".to_owned(),
+            vec![(0, 0), (2, 1)],
+            vec![(1, 1)],
+        );
+
+        assert_eq!(
+            Viewport::new(1, 0, 3, 3).crop(&grid).display(),
+            ". . .\nx * .\n. . ."
+        );
+        assert_eq!(
+            Viewport::new(-1, -1, 2, 2).crop(&grid).display(),
+            ". .\n. *"
+        );
+    }
+
+    #[test]
+    fn bounds_of_alive_cells() {
+        assert_eq!(bounds(vec![(3, -1), (-2, 4), (0, 0)]), Some((-2, -1, 3, 4)));
+        assert_eq!(bounds(vec![]), None);
+    }
+
+    #[test]
+    fn view_size_depends_on_render_mode_and_zoom() {
+        let view = View::new(1, false);
+
+        assert_eq!(view.size(80, 20, RenderMode::Text), (40, 20));
+        assert_eq!(view.size(80, 20, RenderMode::HalfBlock), (80, 40));
+        assert_eq!(view.size(80, 20, RenderMode::Braille), (160, 80));
+        assert_eq!(
+            View::new(4, false).size(80, 20, RenderMode::Braille),
+            (160, 80)
+        );
+        assert_eq!(
+            View::new(4, false).characters(160, 80, RenderMode::Text),
+            (80, 20)
+        );
+        assert_eq!(view.characters(161, 81, RenderMode::Braille), (81, 21));
+    }
+
+    #[test]
+    fn zooming_keeps_the_middle_in_place() {
+        let mut view = View::new(1, false);
+        view.centre_on((100, 50), 40, 10, RenderMode::Text);
+
+        assert_eq!((view.x, view.y), (90, 45));
+
+        view.set_zoom(4, 40, 10, RenderMode::Text);
+
+        assert_eq!((view.x, view.y), (60, 30));
+    }
+
+    #[test]
+    fn fit_zooms_out_until_everything_is_shown() {
+        let mut view = View::new(1, false);
+
+        view.fit((0, 0, 399, 99), 80, 25, RenderMode::Text);
+
+        assert_eq!(view.zoom, 10);
+        assert_eq!((view.x, view.y), (-1, -76));
+    }
+
+    /*
+
+       * * * . . . .
+       . . . . . . .
+       . . . . . * *
+       . . . . . * *
+
+    */
+    #[test]
+    #[rustfmt::skip]
+    fn zoomed_out_blocks_are_shaded_by_how_many_cells_are_alive() {
+        let grid = Grid::new_alive_grid(
+            7, 4,
+            String::new(), String::new(), String::new(),
+            vec![
+                (0, 0), (1, 0), (2, 0),
+                                                (5, 2), (6, 2),
+                                                (5, 3), (6, 3),
+            ],
+            vec![],
+        );
+        let game = GameOfLife { grid, seed: 0 };
+
+        let view = View::new(2, false);
+        let lines: Vec<String> = view
+            .glyphs(&game, 8, 2, RenderMode::Text, None)
+            .iter()
+            .map(|line| line.iter().map(|glyph| glyph.c).collect())
+            .collect();
+
+        assert_eq!(lines, vec!["▒ ░    ", "    ▒ ▒"]);
+    }
+
+    #[test]
+    fn following_centres_on_the_alive_cells() {
+        let grid = Grid::new_alive_grid(
+            20,
+            20,
+            String::new(),
+            String::new(),
+            String::new(),
+            vec![(10, 12), (14, 16)],
+            vec![],
+        );
+        let game = GameOfLife { grid, seed: 0 };
+
+        let mut view = View::new(1, true);
+        view.track(&game, 8, 4, RenderMode::Text);
+
+        assert_eq!((view.x, view.y), (10, 12));
+
+        view.pan(1, 0);
+        view.track(&game, 8, 4, RenderMode::Text);
+
+        assert_eq!((view.x, view.y), (11, 12));
+    }
+}
@@ -1,12 +1,236 @@
+use crate::bit_grid::BitGrid;
+use crate::brians_brain::BriansBrain;
+use crate::game_of_life::GameOfLife;
+use crate::generations::Generations;
 use crate::grid::Grid;
+use crate::hashlife::HashLife;
+use crate::life_like::LifeLike;
+use crate::macrocell::Macrocell;
+use crate::packed_life::PackedLife;
+use crate::pattern::Pattern;
+use crate::rule::{GenerationsRule, Rule};
+use crate::sparse_life::SparseLife;
+use crate::viewport::{self, Bounds, Viewport};
 
-pub trait Simulation {
+pub trait Simulation: SimulationClone {
     fn seed(&self) -> &u32;
     fn grid(&self) -> &Grid;
+    // The rule in the notation Golly uses, e.g. B3/S23 or /2/3.
+    fn rule(&self) -> String;
 
     fn next(&mut self);
+
+    // As `next`, splitting the work across threads where the engine supports it.
+    // The result is the same as `next` for any number of threads.
+    fn next_parallel(&mut self, _threads: usize) {
+        self.next()
+    }
+
+    fn population(&self) -> u64 {
+        self.grid()
+            .cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.alive)
+            .count() as u64
+    }
+
+    // Whether cells keep how many generations they've been alive, for drawing by age.
+    // Engines which don't track age count every alive cell as new.
+    fn tracks_age(&self) -> bool {
+        true
+    }
+
+    // How many generations in a row each cell of the grid has been alive, row by row,
+    // zero for cells which aren't.
+    #[cfg(test)]
+    fn ages(&self) -> Vec<Vec<u32>> {
+        self.grid()
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.age).collect())
+            .collect()
+    }
+
+    // The cells inside a viewport, in coordinates where (0, 0) is the top left of `grid`.
+    // Engines whose world is larger than the grid show cells past its edges.
+    fn view(&self, viewport: &Viewport) -> Grid {
+        viewport.crop(self.grid())
+    }
+
+    // Every alive cell, in the same coordinates as `view`.
+    fn alive_positions(&self) -> Vec<(i64, i64)> {
+        self.grid()
+            .cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.alive)
+            .map(|cell| (cell.x as i64, cell.y as i64))
+            .collect()
+    }
+
+    // The box holding every alive cell, in the same coordinates as `view`.
+    fn bounds(&self) -> Option<Bounds> {
+        viewport::bounds(self.alive_positions())
+    }
+
+    // The whole world, which for some engines is larger than the grid shown.
+    fn pattern(&self) -> Pattern {
+        Pattern::from_grid(self.grid(), Some(self.rule()))
+    }
+
+    // How many generations each call to `next` advances the world.
+    fn generations_per_step(&self) -> u64 {
+        1
+    }
+
+    // Advances the world by a number of generations, which engines stepping more than one
+    // generation at a time can do exactly. Others step as close as they can without going past.
+    fn advance(&mut self, generations: u64) {
+        for _ in 0..generations / self.generations_per_step() {
+            self.next();
+        }
+    }
+}
+
+// Lets a boxed Simulation be cloned, e.g. to keep earlier generations to step back to.
+pub trait SimulationClone {
+    fn clone_box(&self) -> Box<dyn Simulation>;
+}
+
+impl<T: 'static + Simulation + Clone> SimulationClone for T {
+    fn clone_box(&self) -> Box<dyn Simulation> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Simulation> {
+    fn clone(&self) -> Box<dyn Simulation> {
+        self.clone_box()
+    }
 }
 
+#[derive(Clone)]
 pub struct World {
     pub game: Box<dyn Simulation>,
 }
+
+impl World {
+    // Builds a world from either a named ruleset (game_of_life, brians_brain),
+    // a Life-like rulestring such as "B36/S23" or a Generations rulestring such as "345/2/4".
+    pub fn from_ruleset(ruleset: &str, grid: Grid, seed: u32) -> Result<World, String> {
+        let game: Box<dyn Simulation> = match ruleset {
+            rule if is_game_of_life(rule) => Box::new(GameOfLife { grid, seed }),
+            rule if rule.starts_with("brian") => Box::new(BriansBrain { grid, seed }),
+            rule => match (Rule::parse(rule), GenerationsRule::parse(rule)) {
+                (Ok(rule), _) => Box::new(LifeLike { grid, seed, rule }),
+                (_, Ok(rule)) => Box::new(Generations { grid, seed, rule }),
+                _ => return Err(format!("Unknown ruleset: {}", ruleset)),
+            },
+        };
+
+        Ok(World { game })
+    }
+
+    // Builds a world on an unbounded plane, for game_of_life or a Life-like rulestring.
+    // The grid gives the starting cells and the initial viewport.
+    pub fn unbounded(ruleset: &str, grid: Grid, seed: u32) -> Result<World, String> {
+        Ok(World {
+            game: Box::new(SparseLife::from_grid(grid, life_like_rule(ruleset)?, seed)),
+        })
+    }
+
+    // As `unbounded`, but stepped with HashLife, 2^step_exponent generations at a time.
+    // The pattern can be far larger than a grid; at most 100 by 100 cells from its middle are shown.
+    pub fn hashlife(
+        ruleset: &str,
+        pattern: &Pattern,
+        template: Grid,
+        seed: u32,
+        step_exponent: u8,
+    ) -> Result<World, String> {
+        Ok(World {
+            game: Box::new(HashLife::from_pattern(
+                pattern,
+                life_like_rule(ruleset)?,
+                seed,
+                hashlife_step_exponent(step_exponent)?,
+                template,
+                100,
+                100,
+            )),
+        })
+    }
+
+    // As `hashlife`, but built straight from the quadtree of a macrocell file.
+    pub fn hashlife_from_macrocell(
+        ruleset: &str,
+        tree: &Macrocell,
+        template: Grid,
+        seed: u32,
+        step_exponent: u8,
+    ) -> Result<World, String> {
+        Ok(World {
+            game: Box::new(HashLife::from_macrocell(
+                tree,
+                life_like_rule(ruleset)?,
+                seed,
+                hashlife_step_exponent(step_exponent)?,
+                template,
+                100,
+                100,
+            )),
+        })
+    }
+
+    // Builds a bounded world stored as a BitGrid, for game_of_life or a Life-like rulestring.
+    // At most 100 by 100 cells from the middle of the world are shown.
+    pub fn packed(
+        ruleset: &str,
+        bits: BitGrid,
+        template: Grid,
+        seed: u32,
+    ) -> Result<World, String> {
+        let rule = match ruleset {
+            rule if is_game_of_life(rule) => Rule::conway(),
+            rule => Rule::parse(rule)
+                .map_err(|_| format!("Unknown Life-like ruleset for a packed world: {}", rule))?,
+        };
+
+        Ok(World {
+            game: Box::new(PackedLife::new(bits, rule, seed, template, 100, 100)),
+        })
+    }
+}
+
+// Rules with B0 would bring the whole infinite plane alive, so can't be used unbounded.
+pub fn life_like_rule(ruleset: &str) -> Result<Rule, String> {
+    let rule = match ruleset {
+        rule if is_game_of_life(rule) => Rule::conway(),
+        rule => Rule::parse(rule)
+            .map_err(|_| format!("Unknown Life-like ruleset for an unbounded world: {}", rule))?,
+    };
+
+    if rule.birth.contains(&0) {
+        Err(format!(
+            "B0 rules can't be used for an unbounded world: {}",
+            rule
+        ))
+    } else {
+        Ok(rule)
+    }
+}
+
+fn hashlife_step_exponent(step_exponent: u8) -> Result<u8, String> {
+    match step_exponent {
+        k if k <= 60 => Ok(k),
+        k => Err(format!("Step exponent must be at most 60, got: {}", k)),
+    }
+}
+
+// Golly calls Conway's Game of Life "Life".
+fn is_game_of_life(ruleset: &str) -> bool {
+    ruleset.starts_with("game_of")
+        || ruleset.starts_with("gameof")
+        || ruleset.eq_ignore_ascii_case("life")
+}